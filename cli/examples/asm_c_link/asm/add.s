@@ -0,0 +1,9 @@
+# long add_numbers(long a, long b), System V AMD64 calling convention.
+    .text
+    .globl add_numbers
+add_numbers:
+    movq %rdi, %rax
+    addq %rsi, %rax
+    ret
+
+    .section .note.GNU-stack,"",@progbits
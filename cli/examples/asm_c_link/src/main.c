@@ -0,0 +1,8 @@
+#include <stdio.h>
+
+long add_numbers(long a, long b);
+
+int main(void) {
+    printf("40 + 2 = %ld\n", add_numbers(40, 2));
+    return 0;
+}
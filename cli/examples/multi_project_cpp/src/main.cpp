@@ -0,0 +1,9 @@
+#include <iostream>
+
+#include "math.h"
+
+int main() {
+    std::cout << "2 + 3 = " << add(2, 3) << "\n";
+    std::cout << "2 * 3 = " << mul(2, 3) << "\n";
+    return 0;
+}
@@ -0,0 +1,102 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// A sample project from the crate's `examples/` tree, embedded in the binary.
+pub struct Example {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Paths relative to the example directory, with their contents.
+    pub files: &'static [(&'static str, &'static str)],
+}
+
+macro_rules! example_files {
+    ($dir:literal: $($path:literal),+ $(,)?) => {
+        &[$(($path, include_str!(concat!("../examples/", $dir, "/", $path)))),+]
+    };
+}
+
+pub const EXAMPLES: &[Example] = &[
+    Example {
+        name: "hello_world",
+        description: "A single workspace that prints a greeting and runs a shell command",
+        files: example_files!("hello_world": "main.ms"),
+    },
+    Example {
+        name: "multi_project_cpp",
+        description: "A C++ library and application built with handlers and retry",
+        files: example_files!(
+            "multi_project_cpp": "build.ms",
+            "lib/math.h",
+            "lib/add.cpp",
+            "lib/mul.cpp",
+            "src/main.cpp",
+        ),
+    },
+    Example {
+        name: "asm_c_link",
+        description: "Assembly and C sources compiled separately and linked together",
+        files: example_files!("asm_c_link": "build.ms", "asm/add.s", "src/main.c"),
+    },
+    Example {
+        name: "shared_stages",
+        description: "Reusable stages in their own file and a workspace that includes them",
+        files: example_files!("shared_stages": "main.ms", "stages/greeter.ms"),
+    },
+];
+
+pub fn find(name: &str) -> Option<&'static Example> {
+    EXAMPLES.iter().find(|e| e.name == name)
+}
+
+/// Copies an example into `dest/<name>`, refusing to overwrite existing files.
+/// Returns the directory the example was written to.
+pub fn copy(example: &Example, dest: &Path) -> io::Result<PathBuf> {
+    let root = dest.join(example.name);
+    for (path, _) in example.files {
+        let target = root.join(path);
+        if target.exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} already exists", target.display()),
+            ));
+        }
+    }
+    for (path, contents) in example.files {
+        let target = root.join(path);
+        if let Some(dir) = target.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&target, contents)?;
+    }
+    Ok(root)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mainstage_core::analyzer;
+    use mainstage_core::formatter::format_script;
+    use mainstage_core::prelude::*;
+
+    #[test]
+    fn examples_parse_are_formatted_and_lint_clean() {
+        for example in EXAMPLES {
+            for (path, contents) in example.files.iter().filter(|(p, _)| p.ends_with(".ms")) {
+                let name = format!("{}/{}", example.name, path);
+                let script = Script::from_source(PathBuf::from(&name), contents.to_string());
+                let ast = generate_ast_from_source(&script)
+                    .unwrap_or_else(|e| panic!("{} does not parse: {}", name, e));
+
+                let formatted = format_script(&script).expect("example formats");
+                assert_eq!(&formatted, contents, "{} is not formatted", name);
+                let again = format_script(&Script::from_source(PathBuf::from(&name), formatted))
+                    .expect("formatted example formats");
+                assert_eq!(&again, contents, "formatting {} is not idempotent", name);
+
+                let findings = analyzer::check(&ast);
+                assert!(findings.is_empty(), "{}: {:?}", name, findings);
+            }
+        }
+    }
+}
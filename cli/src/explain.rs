@@ -0,0 +1,407 @@
+use mainstage_core::analyzer::entry_point;
+use mainstage_core::prelude::*;
+use mainstage_core::project::{Project, SourceFile};
+
+/// Builds a plain-language overview of a loaded script: its entry workspace, the order the
+/// stages run in, and the projects, imports and includes it relies on. The first file in
+/// `project` is the script being explained; declarations from the files it includes are
+/// reported alongside its own.
+pub fn explain(project: &Project) -> String {
+    let root = &project.files[0];
+    let items: Vec<(&AstNode, &SourceFile)> = project
+        .files
+        .iter()
+        .flat_map(|file| {
+            file.ast
+                .children()
+                .into_iter()
+                .map(move |item| (item, file))
+        })
+        .collect();
+    let workspaces: Vec<&AstNode> = items
+        .iter()
+        .map(|(item, _)| *item)
+        .filter(|i| matches!(i.get_kind(), AstNodeKind::Workspace { .. }))
+        .collect();
+    let projects: Vec<(&AstNode, &SourceFile)> = items
+        .iter()
+        .copied()
+        .filter(|(i, _)| matches!(i.get_kind(), AstNodeKind::Project { .. }))
+        .collect();
+    let declared_stages: Vec<(&AstNode, &SourceFile)> = items
+        .iter()
+        .copied()
+        .filter(|(i, _)| matches!(i.get_kind(), AstNodeKind::Stage { .. }))
+        .collect();
+    let stages: Vec<&AstNode> = declared_stages.iter().map(|(stage, _)| *stage).collect();
+
+    let mut out = format!("{}\n\n", root.script.path.display());
+
+    let mut called = Vec::new();
+    match entry_point(&root.ast) {
+        Some(entry) => {
+            let (kind, name, body) = match entry.get_kind() {
+                AstNodeKind::Workspace { name, body, .. } => ("workspace", name, body),
+                AstNodeKind::Project { name, body, .. } => ("project", name, body),
+                AstNodeKind::Stage { name, body, .. } => ("stage", name, body),
+                _ => unreachable!("entry points are declarations"),
+            };
+            out.push_str(&format!("Entry {}: {}", kind, name));
+            if let Some(line) = entry.get_location().map(|l| l.line) {
+                out.push_str(&format!(" (line {})", line));
+            }
+            out.push('\n');
+            let others: Vec<String> = workspaces
+                .iter()
+                .filter(|w| !std::ptr::eq(**w, entry))
+                .filter_map(|w| decl_name(w))
+                .collect();
+            if !others.is_empty() {
+                out.push_str(&format!(
+                    "  Other workspaces ({}) are not run by default: {}\n",
+                    others.len(),
+                    others.join(", ")
+                ));
+            }
+
+            let mut order = Vec::new();
+            stage_calls(body, &stages, None, &mut order);
+            if order.is_empty() {
+                out.push_str("  It does not call any stages.\n");
+            } else {
+                out.push_str("  Stages run in this order:\n");
+                for (i, (stage, caller)) in order.iter().enumerate() {
+                    match caller {
+                        Some(caller) => out.push_str(&format!(
+                            "    {}. {} (called from {})\n",
+                            i + 1,
+                            stage,
+                            caller
+                        )),
+                        None => out.push_str(&format!("    {}. {}\n", i + 1, stage)),
+                    }
+                }
+            }
+            called = order.into_iter().map(|(stage, _)| stage).collect();
+            if kind == "stage" {
+                called.push(name.clone());
+            }
+            for (event, handler) in handlers(body) {
+                let mut handler_order = Vec::new();
+                stage_calls(handler, &stages, None, &mut handler_order);
+                let handler_stages: Vec<String> =
+                    handler_order.into_iter().map(|(stage, _)| stage).collect();
+                if handler_stages.is_empty() {
+                    out.push_str(&format!("  Declares an {} handler.\n", event));
+                } else {
+                    out.push_str(&format!(
+                        "  Declares an {} handler that runs {}.\n",
+                        event,
+                        handler_stages.join(", ")
+                    ));
+                }
+                called.extend(handler_stages);
+            }
+        }
+        None => out.push_str(
+            "No workspace or [entrypoint] is declared, so nothing runs when the script starts.\n",
+        ),
+    }
+    out.push('\n');
+
+    if projects.is_empty() {
+        out.push_str("Projects: none\n");
+    } else {
+        out.push_str("Projects:\n");
+        for (project, file) in &projects {
+            let AstNodeKind::Project { name, body, .. } = project.get_kind() else {
+                continue;
+            };
+            let settings: Vec<String> = body
+                .children()
+                .into_iter()
+                .filter_map(|s| match s.get_kind() {
+                    AstNodeKind::Assignment { target, .. } => match target.get_kind() {
+                        AstNodeKind::Identifier { name } => Some(name.clone()),
+                        _ => None,
+                    },
+                    _ => None,
+                })
+                .collect();
+            let origin = origin(file, root);
+            if settings.is_empty() {
+                out.push_str(&format!("  {}{}\n", name, origin));
+            } else {
+                out.push_str(&format!(
+                    "  {}{} — sets {}\n",
+                    name,
+                    origin,
+                    settings.join(", ")
+                ));
+            }
+        }
+    }
+
+    if stages.is_empty() {
+        out.push_str("Stages: none\n");
+    } else {
+        out.push_str("Stages:\n");
+        for (stage, file) in &declared_stages {
+            let AstNodeKind::Stage { name, args, .. } = stage.get_kind() else {
+                continue;
+            };
+            let params = args
+                .as_ref()
+                .map(|a| {
+                    a.children()
+                        .into_iter()
+                        .filter_map(|p| match p.get_kind() {
+                            AstNodeKind::Identifier { name } => Some(name.as_str()),
+                            _ => None,
+                        })
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                })
+                .unwrap_or_default();
+            let note = if called.contains(name) {
+                ""
+            } else {
+                " — never called from the entry point"
+            };
+            out.push_str(&format!(
+                "  {}({}){}{}\n",
+                name,
+                params,
+                origin(file, root),
+                note
+            ));
+        }
+    }
+
+    let mut imports = Vec::new();
+    let mut includes = Vec::new();
+    for file in &project.files {
+        collect_dependencies(&file.ast, &mut imports, &mut includes);
+    }
+    if imports.is_empty() {
+        out.push_str("Plugins required: none\n");
+    } else {
+        out.push_str("Plugins required:\n");
+        for (module, alias) in imports {
+            out.push_str(&format!("  {} (used as {})\n", module, alias));
+        }
+    }
+    if !includes.is_empty() {
+        out.push_str(&format!("Includes: {}\n", includes.join(", ")));
+    }
+    out
+}
+
+/// Notes which included file a declaration comes from; empty for the root script.
+fn origin(file: &SourceFile, root: &SourceFile) -> String {
+    if std::ptr::eq(file, root) {
+        String::new()
+    } else {
+        format!(" (from {})", file.script.path.display())
+    }
+}
+
+fn decl_name(node: &AstNode) -> Option<String> {
+    match node.get_kind() {
+        AstNodeKind::Workspace { name, .. }
+        | AstNodeKind::Project { name, .. }
+        | AstNodeKind::Stage { name, .. } => Some(name.clone()),
+        _ => None,
+    }
+}
+
+fn handlers(body: &AstNode) -> Vec<(&str, &AstNode)> {
+    body.children()
+        .into_iter()
+        .filter_map(|s| match s.get_kind() {
+            AstNodeKind::Handler { event, body } => Some((event.as_str(), body.as_ref())),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Appends the stages called below `node` in source order, following each stage into its
+/// own body the first time it is reached. Handlers are skipped since they only run at the end.
+fn stage_calls(
+    node: &AstNode,
+    stages: &[&AstNode],
+    caller: Option<&str>,
+    order: &mut Vec<(String, Option<String>)>,
+) {
+    if matches!(node.get_kind(), AstNodeKind::Handler { .. }) {
+        return;
+    }
+    for child in node.children() {
+        stage_calls(child, stages, caller, order);
+    }
+    let AstNodeKind::Call { callee, .. } = node.get_kind() else {
+        return;
+    };
+    let AstNodeKind::Identifier { name } = callee.get_kind() else {
+        return;
+    };
+    let Some(stage) = stages
+        .iter()
+        .find(|s| decl_name(s).as_deref() == Some(name))
+    else {
+        return;
+    };
+    if order.iter().any(|(n, _)| n == name) {
+        return;
+    }
+    order.push((name.clone(), caller.map(str::to_string)));
+    if let AstNodeKind::Stage { body, .. } = stage.get_kind() {
+        stage_calls(body, stages, Some(name), order);
+    }
+}
+
+fn collect_dependencies(
+    node: &AstNode,
+    imports: &mut Vec<(String, String)>,
+    includes: &mut Vec<String>,
+) {
+    match node.get_kind() {
+        AstNodeKind::Import { module, alias } => imports.push((module.clone(), alias.clone())),
+        AstNodeKind::Include { file } => includes.push(file.clone()),
+        _ => {}
+    }
+    for child in node.children() {
+        collect_dependencies(child, imports, includes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// A project made of in-memory files; the first one is the script being explained.
+    fn project(files: &[(&str, &str)]) -> Project {
+        let files = files
+            .iter()
+            .map(|(path, source)| {
+                let script = Script::from_source(PathBuf::from(path), source.to_string());
+                let ast = generate_ast_from_source(&script).expect("source should parse");
+                SourceFile { script, ast }
+            })
+            .collect();
+        Project { files }
+    }
+
+    #[test]
+    fn stages_are_listed_in_the_order_they_run() {
+        let out = explain(&project(&[(
+            "build.ms",
+            "workspace main {\n    build();\n    test();\n}\n\
+             stage build() { compile(); }\n\
+             stage compile() {}\n\
+             stage test() { build(); }\n",
+        )]));
+        assert!(
+            out.contains(
+                "Entry workspace: main (line 1)\n  Stages run in this order:\n\
+                 \x20   1. build\n    2. compile (called from build)\n    3. test\n"
+            ),
+            "{}",
+            out
+        );
+    }
+
+    #[test]
+    fn handlers_and_their_stages_are_reported() {
+        let out = explain(&project(&[(
+            "build.ms",
+            "workspace main {\n    build();\n    on_failure { notify(); }\n    on_success {}\n}\n\
+             stage build() {}\n\
+             stage notify() {}\n",
+        )]));
+        assert!(
+            out.contains("  Declares an on_failure handler that runs notify.\n"),
+            "{}",
+            out
+        );
+        assert!(
+            out.contains("  Declares an on_success handler.\n"),
+            "{}",
+            out
+        );
+        assert!(out.contains("  notify()\n"), "{}", out);
+    }
+
+    #[test]
+    fn uncalled_stages_are_noted() {
+        let out = explain(&project(&[(
+            "build.ms",
+            "workspace main { build(); }\nworkspace other { clean(); }\n\
+             stage build(target) {}\nstage clean() {}\n",
+        )]));
+        assert!(
+            out.contains("  Other workspaces (1) are not run by default: other\n"),
+            "{}",
+            out
+        );
+        assert!(
+            out.contains(
+                "Stages:\n  build(target)\n  clean() — never called from the entry point\n"
+            ),
+            "{}",
+            out
+        );
+    }
+
+    #[test]
+    fn script_without_an_entry_point() {
+        let out = explain(&project(&[("lib.ms", "stage build() {}\n")]));
+        assert!(
+            out.contains("No workspace or [entrypoint] is declared"),
+            "{}",
+            out
+        );
+        assert!(
+            out.contains("  build() — never called from the entry point\n"),
+            "{}",
+            out
+        );
+    }
+
+    #[test]
+    fn declarations_from_included_files_are_reported() {
+        let out = explain(&project(&[
+            (
+                "main.ms",
+                "include \"shared.ms\";\nworkspace main { greet(); }\n",
+            ),
+            (
+                "shared.ms",
+                "import \"tools\" as tools;\nproject app { name = \"app\"; }\n\
+                 stage greet() { tools.say(); }\nstage unused() {}\n",
+            ),
+        ]));
+        assert!(out.starts_with("main.ms\n\n"), "{}", out);
+        assert!(out.contains("    1. greet\n"), "{}", out);
+        assert!(
+            out.contains("Projects:\n  app (from shared.ms) — sets name\n"),
+            "{}",
+            out
+        );
+        assert!(
+            out.contains(
+                "Stages:\n  greet() (from shared.ms)\n  \
+                 unused() (from shared.ms) — never called from the entry point\n"
+            ),
+            "{}",
+            out
+        );
+        assert!(
+            out.contains("Plugins required:\n  tools (used as tools)\n"),
+            "{}",
+            out
+        );
+        assert!(out.contains("Includes: shared.ms\n"), "{}", out);
+    }
+}
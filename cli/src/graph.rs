@@ -0,0 +1,136 @@
+use mainstage_core::analyzer::{CallGraph, GraphNode, GraphNodeKind};
+
+pub const FORMATS: [&str; 3] = ["dot", "mermaid", "json"];
+
+pub fn render(graph: &CallGraph, format: &str) -> String {
+    match format {
+        "dot" => dot(graph),
+        "mermaid" => mermaid(graph),
+        _ => serde_json::to_string_pretty(graph).expect("call graph is serializable"),
+    }
+}
+
+/// Node identifiers safe for both DOT and Mermaid: `stage:build` becomes `stage_build`.
+fn ident(id: &str) -> String {
+    id.replace(':', "_")
+}
+
+fn label(node: &GraphNode) -> String {
+    match node.kind {
+        GraphNodeKind::Workspace => format!("workspace {}", node.name),
+        GraphNodeKind::Stage => node.name.clone(),
+        GraphNodeKind::Plugin => format!("plugin {}", node.name),
+    }
+}
+
+fn dot(graph: &CallGraph) -> String {
+    let mut out = String::from("digraph mainstage {\n    rankdir=LR;\n");
+    for node in &graph.nodes {
+        let shape = match node.kind {
+            GraphNodeKind::Workspace => "doubleoctagon",
+            GraphNodeKind::Stage => "box",
+            GraphNodeKind::Plugin => "component",
+        };
+        out.push_str(&format!(
+            "    {} [label=\"{}\", shape={}];\n",
+            ident(&node.id),
+            label(node),
+            shape
+        ));
+    }
+    for edge in &graph.edges {
+        out.push_str(&format!("    {} -> {}", ident(&edge.from), ident(&edge.to)));
+        if let Some(function) = &edge.function {
+            out.push_str(&format!(" [label=\"{}\"]", function));
+        }
+        out.push_str(";\n");
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn mermaid(graph: &CallGraph) -> String {
+    let mut out = String::from("flowchart LR\n");
+    for node in &graph.nodes {
+        let text = label(node);
+        let shape = match node.kind {
+            GraphNodeKind::Workspace => format!("(({}))", text),
+            GraphNodeKind::Stage => format!("[{}]", text),
+            GraphNodeKind::Plugin => format!("[[{}]]", text),
+        };
+        out.push_str(&format!("    {}{}\n", ident(&node.id), shape));
+    }
+    for edge in &graph.edges {
+        match &edge.function {
+            Some(function) => out.push_str(&format!(
+                "    {} -->|{}| {}\n",
+                ident(&edge.from),
+                function,
+                ident(&edge.to)
+            )),
+            None => out.push_str(&format!(
+                "    {} --> {}\n",
+                ident(&edge.from),
+                ident(&edge.to)
+            )),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mainstage_core::prelude::*;
+    use std::path::PathBuf;
+
+    const SOURCE: &str = "import \"tools\" as tools;\n\
+                          workspace main { build(); }\n\
+                          stage build() { build(); tools.lint(); missing(); }\n";
+
+    fn graph() -> CallGraph {
+        let script = Script::from_source(PathBuf::from("test.ms"), SOURCE.to_string());
+        let ast = generate_ast_from_source(&script).expect("source should parse");
+        CallGraph::from_ast(&ast)
+    }
+
+    #[test]
+    fn dot_lists_nodes_then_edges() {
+        assert_eq!(
+            render(&graph(), "dot"),
+            "digraph mainstage {\n    rankdir=LR;\n\
+             \x20   plugin_tools [label=\"plugin tools\", shape=component];\n\
+             \x20   workspace_main [label=\"workspace main\", shape=doubleoctagon];\n\
+             \x20   stage_build [label=\"build\", shape=box];\n\
+             \x20   workspace_main -> stage_build;\n\
+             \x20   stage_build -> stage_build;\n\
+             \x20   stage_build -> plugin_tools [label=\"lint\"];\n\
+             }\n"
+        );
+    }
+
+    #[test]
+    fn mermaid_labels_plugin_edges() {
+        assert_eq!(
+            render(&graph(), "mermaid"),
+            "flowchart LR\n\
+             \x20   plugin_tools[[plugin tools]]\n\
+             \x20   workspace_main((workspace main))\n\
+             \x20   stage_build[build]\n\
+             \x20   workspace_main --> stage_build\n\
+             \x20   stage_build --> stage_build\n\
+             \x20   stage_build -->|lint| plugin_tools\n"
+        );
+    }
+
+    #[test]
+    fn json_keeps_ids_and_functions() {
+        let json: serde_json::Value =
+            serde_json::from_str(&render(&graph(), "json")).expect("output is JSON");
+        assert_eq!(json["nodes"][0]["id"], "plugin:tools");
+        assert_eq!(json["nodes"][0]["kind"], "plugin");
+        assert_eq!(json["edges"].as_array().map(Vec::len), Some(3));
+        assert_eq!(json["edges"][2]["function"], "lint");
+        assert_eq!(json["edges"][0]["function"], serde_json::Value::Null);
+    }
+}
@@ -0,0 +1,860 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+
+use mainstage_core::analyzer;
+use mainstage_core::prelude::*;
+use serde_json::{Value, json};
+
+/// LSP `SymbolKind` values used for document symbols.
+const SYMBOL_MODULE: u32 = 2;
+const SYMBOL_NAMESPACE: u32 = 3;
+const SYMBOL_FIELD: u32 = 8;
+const SYMBOL_FUNCTION: u32 = 12;
+
+/// JSON-RPC error code for requests received after `shutdown`.
+const INVALID_REQUEST: i64 = -32600;
+/// JSON-RPC error code for unsupported requests.
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// Runs the language server on stdin/stdout until the client sends `exit`. Returns the
+/// process exit code: `0` when `shutdown` came first, `1` otherwise.
+pub fn serve() -> io::Result<i32> {
+    let stdin = io::stdin();
+    let mut reader = stdin.lock();
+    let mut writer = io::stdout();
+    let mut server = Server::default();
+
+    while let Some(message) = read_message(&mut reader)? {
+        if let Some(code) = server.handle(&message, &mut writer)? {
+            return Ok(code);
+        }
+    }
+    Ok(server.exit_code())
+}
+
+/// Reads one `Content-Length` framed JSON-RPC message. Returns `None` on end of input.
+fn read_message(reader: &mut impl BufRead) -> io::Result<Option<Value>> {
+    let mut content_length = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(value) = header.strip_prefix("Content-Length:") {
+            content_length = value.trim().parse::<usize>().ok();
+        }
+    }
+
+    let Some(length) = content_length else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "message without Content-Length header",
+        ));
+    };
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    serde_json::from_slice(&body)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn write_message(writer: &mut impl Write, message: &Value) -> io::Result<()> {
+    let body = message.to_string();
+    write!(writer, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    writer.flush()
+}
+
+#[derive(Default)]
+struct Server {
+    /// Open documents keyed by URI; the server uses full-text synchronization.
+    documents: HashMap<String, String>,
+    /// Set by `shutdown`; only `exit` is accepted afterwards.
+    shut_down: bool,
+}
+
+impl Server {
+    /// Handles one message. Returns the exit code once the client asked the server to exit.
+    fn handle(&mut self, message: &Value, writer: &mut impl Write) -> io::Result<Option<i32>> {
+        let method = message["method"].as_str().unwrap_or_default();
+        let params = &message["params"];
+        let id = message.get("id").cloned();
+
+        if method == "exit" {
+            return Ok(Some(self.exit_code()));
+        }
+        if self.shut_down {
+            // Notifications are dropped; requests are refused.
+            if let Some(id) = &id {
+                write_message(
+                    writer,
+                    &error_response(id, INVALID_REQUEST, "The server is shutting down."),
+                )?;
+            }
+            return Ok(None);
+        }
+
+        let result = match method {
+            "initialize" => Some(json!({
+                "capabilities": {
+                    "textDocumentSync": 1,
+                    "documentSymbolProvider": true,
+                    "definitionProvider": true,
+                    "hoverProvider": true,
+                },
+                "serverInfo": { "name": "mainstage", "version": env!("CARGO_PKG_VERSION") },
+            })),
+            "shutdown" => {
+                self.shut_down = true;
+                Some(Value::Null)
+            }
+            "textDocument/didOpen" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                let text = params["textDocument"]["text"].as_str().unwrap_or_default();
+                self.documents.insert(uri.to_string(), text.to_string());
+                self.publish_diagnostics(uri, writer)?;
+                None
+            }
+            "textDocument/didChange" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                let text = params["contentChanges"]
+                    .as_array()
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change["text"].as_str());
+                if let Some(text) = text {
+                    self.documents.insert(uri.to_string(), text.to_string());
+                    self.publish_diagnostics(uri, writer)?;
+                }
+                None
+            }
+            "textDocument/didClose" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                self.documents.remove(uri);
+                write_message(
+                    writer,
+                    &notification(
+                        "textDocument/publishDiagnostics",
+                        json!({ "uri": uri, "diagnostics": [] }),
+                    ),
+                )?;
+                None
+            }
+            "textDocument/documentSymbol" => Some(self.document_symbols(params)),
+            "textDocument/definition" => Some(self.definition(params)),
+            "textDocument/hover" => Some(self.hover(params)),
+            _ => {
+                // Unknown notifications are ignored; unknown requests get an error reply.
+                if let Some(id) = &id {
+                    write_message(
+                        writer,
+                        &error_response(
+                            id,
+                            METHOD_NOT_FOUND,
+                            &format!("Unsupported method: {}", method),
+                        ),
+                    )?;
+                }
+                None
+            }
+        };
+
+        if let (Some(id), Some(result)) = (id, result) {
+            write_message(
+                writer,
+                &json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            )?;
+        }
+        Ok(None)
+    }
+
+    /// `exit` is only clean after `shutdown`.
+    fn exit_code(&self) -> i32 {
+        if self.shut_down { 0 } else { 1 }
+    }
+
+    fn text(&self, uri: &str) -> &str {
+        self.documents
+            .get(uri)
+            .map(String::as_str)
+            .unwrap_or_default()
+    }
+
+    fn parse(&self, uri: &str) -> Option<Result<AstNode, Box<dyn MainstageErrorExt>>> {
+        let text = self.documents.get(uri)?;
+        let script = Script::from_source(path_from_uri(uri), text.clone());
+        if script.is_empty() {
+            return None;
+        }
+        Some(generate_ast_from_source(&script))
+    }
+
+    fn publish_diagnostics(&self, uri: &str, writer: &mut impl Write) -> io::Result<()> {
+        let text = self.text(uri);
+        let diagnostics = match self.parse(uri) {
+            Some(Ok(ast)) => analyzer::check_with(&ast, &lint_levels(uri))
+                .iter()
+                .map(|d| diagnostic(d, text))
+                .collect(),
+            Some(Err(e)) => vec![diagnostic(e.as_ref(), text)],
+            None => Vec::new(),
+        };
+        write_message(
+            writer,
+            &notification(
+                "textDocument/publishDiagnostics",
+                json!({ "uri": uri, "diagnostics": diagnostics }),
+            ),
+        )
+    }
+
+    fn document_symbols(&self, params: &Value) -> Value {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+        let Some(Ok(ast)) = self.parse(uri) else {
+            return json!([]);
+        };
+        let text = self.text(uri);
+        Value::Array(
+            ast.children()
+                .into_iter()
+                .filter_map(|item| symbol(item, text))
+                .collect(),
+        )
+    }
+
+    fn definition(&self, params: &Value) -> Value {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+        let Some(Ok(ast)) = self.parse(uri) else {
+            return Value::Null;
+        };
+        let text = self.text(uri);
+        let (line, column) = position(params, text);
+        match resolve(&ast, line, column) {
+            Some(Resolved { definition, .. }) => match definition.get_span() {
+                Some(span) => json!({ "uri": uri, "range": range(span, text) }),
+                None => Value::Null,
+            },
+            None => Value::Null,
+        }
+    }
+
+    fn hover(&self, params: &Value) -> Value {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+        let Some(Ok(ast)) = self.parse(uri) else {
+            return Value::Null;
+        };
+        let (line, column) = position(params, self.text(uri));
+        let Some(path) = node_path_at(&ast, line, column) else {
+            return Value::Null;
+        };
+
+        let text = match resolve(&ast, line, column) {
+            Some(resolved) => resolved.description,
+            None => match path.last() {
+                Some(node) => match kind_name(node) {
+                    Some(kind) => kind.to_string(),
+                    None => return Value::Null,
+                },
+                None => return Value::Null,
+            },
+        };
+        json!({ "contents": { "kind": "markdown", "value": format!("```mainstage\n{}\n```", text) } })
+    }
+}
+
+/// Lint levels from the `mainstage.toml` governing the document; an unreadable or
+/// invalid configuration falls back to the defaults.
+fn lint_levels(uri: &str) -> analyzer::LintLevels {
+    let mut levels = analyzer::LintLevels::default();
+    let path = path_from_uri(uri);
+    if let Some(dir) = path.parent()
+        && let Ok(Some(config)) = Config::discover(dir)
+    {
+        levels.apply(&config.lints);
+    }
+    levels
+}
+
+fn notification(method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "method": method, "params": params })
+}
+
+fn error_response(id: &Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+/// Turns a `file://` URI into a filesystem path, decoding `%XX` escapes.
+fn path_from_uri(uri: &str) -> PathBuf {
+    let raw = uri.strip_prefix("file://").unwrap_or(uri);
+    let bytes = raw.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = match (bytes[i], bytes.get(i + 1..i + 3)) {
+            (b'%', Some(hex)) => std::str::from_utf8(hex)
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+            _ => None,
+        };
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    PathBuf::from(String::from_utf8_lossy(&decoded).to_string())
+}
+
+/// Returns the request position as the 1-based line/column pair used by `Location`.
+/// LSP characters count UTF-16 code units while `Location` columns count characters.
+fn position(params: &Value, text: &str) -> (usize, usize) {
+    let line = params["position"]["line"].as_u64().unwrap_or(0) as usize;
+    let character = params["position"]["character"].as_u64().unwrap_or(0) as usize;
+    let mut units = 0;
+    let mut column = 1;
+    for ch in line_text(text, line + 1).chars() {
+        if units >= character {
+            break;
+        }
+        units += ch.len_utf16();
+        column += 1;
+    }
+    (line + 1, column + character.saturating_sub(units))
+}
+
+fn range(span: &Span, text: &str) -> Value {
+    json!({
+        "start": lsp_position(&span.start, text),
+        "end": lsp_position(&span.end, text),
+    })
+}
+
+/// Converts a `Location` to an LSP position, counting UTF-16 code units on its line.
+fn lsp_position(location: &Location, text: &str) -> Value {
+    let column = location.column.saturating_sub(1);
+    let line = line_text(text, location.line);
+    let character: usize = line.chars().take(column).map(char::len_utf16).sum();
+    let past_end = column.saturating_sub(line.chars().count());
+    json!({ "line": location.line.saturating_sub(1), "character": character + past_end })
+}
+
+/// The 1-based `line` of `text` without its line ending, or `""` past the end.
+fn line_text(text: &str, line: usize) -> &str {
+    text.lines().nth(line.saturating_sub(1)).unwrap_or_default()
+}
+
+fn diagnostic(error: &dyn MainstageErrorExt, text: &str) -> Value {
+    let severity = match error.level() {
+        Level::Warning => 2,
+        Level::Info => 3,
+        _ => 1,
+    };
+    let span = error
+        .span()
+        .or_else(|| error.location().map(|loc| Span::new(loc.clone(), loc)));
+    let range = match span {
+        Some(span) => range(&span, text),
+        None => {
+            json!({ "start": { "line": 0, "character": 0 }, "end": { "line": 0, "character": 0 } })
+        }
+    };
+    json!({
+        "range": range,
+        "severity": severity,
+        "source": "mainstage",
+        "message": error.message(),
+    })
+}
+
+fn symbol(node: &AstNode, text: &str) -> Option<Value> {
+    let span = node.get_span()?;
+    let (name, kind, detail, body) = match node.get_kind() {
+        AstNodeKind::Workspace { name, body, .. } => (name, SYMBOL_NAMESPACE, "workspace", body),
+        AstNodeKind::Project { name, body, .. } => (name, SYMBOL_MODULE, "project", body),
+        AstNodeKind::Stage { name, body, .. } => (name, SYMBOL_FUNCTION, "stage", body),
+        _ => return None,
+    };
+
+    // Top-level assignments are the members other declarations can reach via `name.member`.
+    let children: Vec<Value> = body
+        .children()
+        .into_iter()
+        .filter_map(|statement| {
+            let (member, member_span) = assigned_name(statement)?;
+            Some(json!({
+                "name": member,
+                "kind": SYMBOL_FIELD,
+                "range": range(member_span, text),
+                "selectionRange": range(member_span, text),
+            }))
+        })
+        .collect();
+
+    Some(json!({
+        "name": name,
+        "detail": detail,
+        "kind": kind,
+        "range": range(span, text),
+        "selectionRange": range(span, text),
+        "children": children,
+    }))
+}
+
+/// Returns the variable name and span of an assignment statement.
+fn assigned_name(node: &AstNode) -> Option<(&str, &Span)> {
+    let AstNodeKind::Assignment { target, .. } = node.get_kind() else {
+        return None;
+    };
+    let AstNodeKind::Identifier { name } = target.get_kind() else {
+        return None;
+    };
+    Some((name, node.get_span()?))
+}
+
+fn contains(span: &Span, line: usize, column: usize) -> bool {
+    (span.start.line, span.start.column) <= (line, column)
+        && (line, column) <= (span.end.line, span.end.column)
+}
+
+/// Returns the chain of nodes from the script root down to the innermost node at the position.
+fn node_path_at(root: &AstNode, line: usize, column: usize) -> Option<Vec<&AstNode>> {
+    let mut path = vec![root];
+    let mut current = root;
+    'descend: loop {
+        for child in current.children() {
+            if child.get_span().is_some_and(|s| contains(s, line, column)) {
+                path.push(child);
+                current = child;
+                continue 'descend;
+            }
+        }
+        break;
+    }
+    if path.len() > 1 { Some(path) } else { None }
+}
+
+fn kind_name(node: &AstNode) -> Option<&'static str> {
+    Some(match node.get_kind() {
+        AstNodeKind::String { .. } => "string",
+        AstNodeKind::Integer { .. } => "integer",
+        AstNodeKind::Float { .. } => "float",
+        AstNodeKind::Bool { .. } => "bool",
+        AstNodeKind::Null => "null",
+        AstNodeKind::List { .. } => "list",
+        AstNodeKind::Command { .. } => "shell command",
+        AstNodeKind::Call { .. } => "call result",
+        AstNodeKind::UnaryOp { .. }
+        | AstNodeKind::PostfixOp { .. }
+        | AstNodeKind::BinaryOp { .. }
+        | AstNodeKind::Member { .. }
+        | AstNodeKind::Index { .. } => "expression",
+        _ => return None,
+    })
+}
+
+struct Resolved<'a> {
+    definition: &'a AstNode,
+    description: String,
+}
+
+fn declaration_signature(node: &AstNode) -> Option<String> {
+    match node.get_kind() {
+        AstNodeKind::Workspace { name, .. } => Some(format!("workspace {}", name)),
+        AstNodeKind::Project { name, .. } => Some(format!("project {}", name)),
+        AstNodeKind::Stage { name, args, .. } => {
+            let params = args
+                .as_ref()
+                .map(|a| {
+                    a.children()
+                        .into_iter()
+                        .filter_map(|p| match p.get_kind() {
+                            AstNodeKind::Identifier { name } => Some(name.as_str()),
+                            _ => None,
+                        })
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                })
+                .unwrap_or_default();
+            Some(format!("stage {}({})", name, params))
+        }
+        _ => None,
+    }
+}
+
+fn find_declaration<'a>(root: &'a AstNode, name: &str) -> Option<&'a AstNode> {
+    root.children()
+        .into_iter()
+        .find(|item| match item.get_kind() {
+            AstNodeKind::Workspace { name: n, .. }
+            | AstNodeKind::Project { name: n, .. }
+            | AstNodeKind::Stage { name: n, .. } => n == name,
+            _ => false,
+        })
+}
+
+/// Finds the first assignment to `name` anywhere below `scope`.
+fn find_assignment<'a>(scope: &'a AstNode, name: &str) -> Option<&'a AstNode> {
+    if let Some((assigned, _)) = assigned_name(scope)
+        && assigned == name
+    {
+        return Some(scope);
+    }
+    scope
+        .children()
+        .into_iter()
+        .find_map(|child| find_assignment(child, name))
+}
+
+fn describe_variable(name: &str, assignment: &AstNode) -> String {
+    let value_kind = match assignment.get_kind() {
+        AstNodeKind::Assignment { value, .. } => kind_name(value).unwrap_or("value"),
+        _ => "value",
+    };
+    format!("{}: {}", name, value_kind)
+}
+
+/// Resolves the identifier or member access at the position to the node that defines it.
+fn resolve(root: &AstNode, line: usize, column: usize) -> Option<Resolved<'_>> {
+    let path = node_path_at(root, line, column)?;
+    let target = *path.last()?;
+    let scope = path.get(1).copied();
+
+    match target.get_kind() {
+        AstNodeKind::Identifier { name } => {
+            if let Some(decl) = find_declaration(root, name) {
+                return Some(Resolved {
+                    definition: decl,
+                    description: declaration_signature(decl)?,
+                });
+            }
+            if let Some(AstNodeKind::Stage {
+                name: stage,
+                args: Some(args),
+                ..
+            }) = scope.map(|s| s.get_kind())
+                && let Some(param) = args.children().into_iter().find(
+                    |p| matches!(p.get_kind(), AstNodeKind::Identifier { name: n } if n == name),
+                )
+            {
+                return Some(Resolved {
+                    definition: param,
+                    description: format!("{}: parameter of stage {}", name, stage),
+                });
+            }
+            // A catch binding is only visible inside its own handler block.
+            if let Some(handler) = path
+                .windows(2)
+                .rev()
+                .find_map(|pair| match pair[0].get_kind() {
+                    AstNodeKind::Try {
+                        binding, handler, ..
+                    } if binding == name && std::ptr::eq(handler.as_ref(), pair[1]) => {
+                        Some(pair[1])
+                    }
+                    _ => None,
+                })
+            {
+                return Some(Resolved {
+                    definition: handler,
+                    description: format!("{}: caught error", name),
+                });
+            }
+            let assignment = find_assignment(scope?, name)?;
+            Some(Resolved {
+                definition: assignment,
+                description: describe_variable(name, assignment),
+            })
+        }
+        // Cursor is on the member name itself (the object was not more specific).
+        AstNodeKind::Member { object, member } => {
+            let owner = match object.get_kind() {
+                AstNodeKind::Identifier { name } => find_declaration(root, name),
+                _ => None,
+            };
+            let candidates: Vec<&AstNode> = match owner {
+                Some(owner) => vec![owner],
+                None => root
+                    .children()
+                    .into_iter()
+                    .filter(|item| matches!(item.get_kind(), AstNodeKind::Project { .. }))
+                    .collect(),
+            };
+            let assignment = candidates.into_iter().find_map(|decl| {
+                decl.children()
+                    .into_iter()
+                    .flat_map(|body| body.children())
+                    .find(|stmt| assigned_name(stmt).is_some_and(|(n, _)| n == member))
+            })?;
+            Some(Resolved {
+                definition: assignment,
+                description: describe_variable(member, assignment),
+            })
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "// caf\u{e9} \u{1f600}\nx = \"\u{1f600}\"; y = 1;\n";
+
+    fn request(line: u64, character: u64) -> Value {
+        json!({ "position": { "line": line, "character": character } })
+    }
+
+    #[test]
+    fn positions_convert_from_utf16() {
+        // `y` follows a 4-byte character that is two UTF-16 code units but one `char`.
+        assert_eq!(position(&request(1, 10), TEXT), (2, 10));
+        assert_eq!(position(&request(1, 0), TEXT), (2, 1));
+        assert_eq!(position(&request(0, 10), TEXT), (1, 10));
+    }
+
+    #[test]
+    fn locations_convert_to_utf16() {
+        let y = Location::new("test.ms".into(), 2, 10);
+        assert_eq!(
+            lsp_position(&y, TEXT),
+            json!({ "line": 1, "character": 10 })
+        );
+        let end_of_line = Location::new("test.ms".into(), 1, 10);
+        assert_eq!(
+            lsp_position(&end_of_line, TEXT),
+            json!({ "line": 0, "character": 10 })
+        );
+    }
+
+    #[test]
+    fn conversions_round_trip() {
+        for (line, column) in [
+            (1, 1),
+            (1, 8),
+            (1, 9),
+            (1, 10),
+            (2, 5),
+            (2, 6),
+            (2, 7),
+            (2, 12),
+        ] {
+            let location = Location::new("test.ms".into(), line, column);
+            let converted = lsp_position(&location, TEXT);
+            let back = position(&json!({ "position": converted }), TEXT);
+            assert_eq!(back, (line, column));
+        }
+    }
+
+    const URI: &str = "file:///mainstage-lsp-test/build.ms";
+    const SOURCE: &str = "workspace main {\n    build(\"x\");\n}\n\
+                          stage build(target) {\n    out = target;\n}\n\
+                          stage clean() {}\n";
+
+    /// Sends one message and returns the exit code and every message written back.
+    fn send(server: &mut Server, message: Value) -> (Option<i32>, Vec<Value>) {
+        let mut out = Vec::new();
+        let code = server
+            .handle(&message, &mut out)
+            .expect("writing to memory succeeds");
+        let mut reader = io::Cursor::new(out);
+        let mut replies = Vec::new();
+        while let Some(reply) = read_message(&mut reader).expect("replies are framed") {
+            replies.push(reply);
+        }
+        (code, replies)
+    }
+
+    fn call(server: &mut Server, id: u64, method: &str, params: Value) -> Value {
+        let (code, replies) = send(
+            server,
+            json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }),
+        );
+        assert_eq!(code, None);
+        assert_eq!(replies.len(), 1, "{:?}", replies);
+        assert_eq!(replies[0]["id"], id);
+        replies[0].clone()
+    }
+
+    fn notify(server: &mut Server, method: &str, params: Value) -> Vec<Value> {
+        let (code, replies) = send(
+            server,
+            json!({ "jsonrpc": "2.0", "method": method, "params": params }),
+        );
+        assert_eq!(code, None);
+        replies
+    }
+
+    fn opened() -> Server {
+        let mut server = Server::default();
+        notify(
+            &mut server,
+            "textDocument/didOpen",
+            json!({ "textDocument": { "uri": URI, "text": SOURCE } }),
+        );
+        server
+    }
+
+    fn at(line: u64, character: u64) -> Value {
+        json!({
+            "textDocument": { "uri": URI },
+            "position": { "line": line, "character": character },
+        })
+    }
+
+    #[test]
+    fn initialize_reports_capabilities() {
+        let reply = call(&mut Server::default(), 1, "initialize", json!({}));
+        let capabilities = &reply["result"]["capabilities"];
+        assert_eq!(capabilities["textDocumentSync"], 1);
+        assert_eq!(capabilities["definitionProvider"], true);
+        assert_eq!(capabilities["hoverProvider"], true);
+        assert_eq!(capabilities["documentSymbolProvider"], true);
+    }
+
+    #[test]
+    fn opening_and_changing_a_document_publishes_diagnostics() {
+        let mut server = Server::default();
+        let published = notify(
+            &mut server,
+            "textDocument/didOpen",
+            json!({ "textDocument": { "uri": URI, "text": SOURCE } }),
+        );
+        assert_eq!(published.len(), 1);
+        assert_eq!(published[0]["method"], "textDocument/publishDiagnostics");
+        let diagnostics = published[0]["params"]["diagnostics"]
+            .as_array()
+            .expect("diagnostics are a list");
+        let unused_stage = diagnostics
+            .iter()
+            .find(|d| d["message"] == "Stage 'clean' is never called from workspace 'main'.")
+            .expect("clean is reported");
+        assert_eq!(unused_stage["severity"], 2);
+        assert_eq!(unused_stage["range"]["start"]["line"], 6);
+        assert!(
+            diagnostics
+                .iter()
+                .any(|d| d["message"] == "Variable 'out' is assigned but never read."),
+            "{:?}",
+            diagnostics
+        );
+
+        let published = notify(
+            &mut server,
+            "textDocument/didChange",
+            json!({
+                "textDocument": { "uri": URI },
+                "contentChanges": [{ "text": "stage broken( {\n" }],
+            }),
+        );
+        let diagnostics = &published[0]["params"]["diagnostics"];
+        assert_eq!(diagnostics.as_array().map(Vec::len), Some(1));
+        assert_eq!(diagnostics[0]["severity"], 1);
+
+        let published = notify(
+            &mut server,
+            "textDocument/didClose",
+            json!({ "textDocument": { "uri": URI } }),
+        );
+        assert_eq!(published[0]["params"]["diagnostics"], json!([]));
+    }
+
+    #[test]
+    fn definition_jumps_to_the_stage() {
+        let mut server = opened();
+        let reply = call(&mut server, 2, "textDocument/definition", at(1, 5));
+        assert_eq!(reply["result"]["uri"], URI);
+        assert_eq!(
+            reply["result"]["range"]["start"],
+            json!({ "line": 3, "character": 0 })
+        );
+
+        let reply = call(&mut server, 3, "textDocument/definition", at(2, 0));
+        assert_eq!(reply["result"], Value::Null);
+    }
+
+    #[test]
+    fn hover_describes_declarations_and_parameters() {
+        let mut server = opened();
+        let reply = call(&mut server, 2, "textDocument/hover", at(1, 5));
+        assert_eq!(
+            reply["result"]["contents"]["value"],
+            "```mainstage\nstage build(target)\n```"
+        );
+        let reply = call(&mut server, 3, "textDocument/hover", at(4, 11));
+        assert_eq!(
+            reply["result"]["contents"]["value"],
+            "```mainstage\ntarget: parameter of stage build\n```"
+        );
+    }
+
+    #[test]
+    fn document_symbols_list_declarations_and_members() {
+        let mut server = opened();
+        let reply = call(
+            &mut server,
+            2,
+            "textDocument/documentSymbol",
+            json!({ "textDocument": { "uri": URI } }),
+        );
+        let symbols = reply["result"].as_array().expect("symbols are a list");
+        let names: Vec<_> = symbols.iter().map(|s| (&s["name"], &s["kind"])).collect();
+        assert_eq!(
+            names,
+            [
+                (&json!("main"), &json!(SYMBOL_NAMESPACE)),
+                (&json!("build"), &json!(SYMBOL_FUNCTION)),
+                (&json!("clean"), &json!(SYMBOL_FUNCTION)),
+            ]
+        );
+        assert_eq!(symbols[1]["children"][0]["name"], "out");
+        assert_eq!(
+            symbols[1]["range"]["start"],
+            json!({ "line": 3, "character": 0 })
+        );
+    }
+
+    #[test]
+    fn unknown_requests_are_refused_and_notifications_ignored() {
+        let mut server = Server::default();
+        let reply = call(&mut server, 1, "workspace/symbol", json!({}));
+        assert_eq!(reply["error"]["code"], METHOD_NOT_FOUND);
+        assert!(notify(&mut server, "$/cancelRequest", json!({ "id": 1 })).is_empty());
+    }
+
+    #[test]
+    fn requests_after_shutdown_are_invalid() {
+        let mut server = opened();
+        let reply = call(&mut server, 1, "shutdown", Value::Null);
+        assert_eq!(reply["result"], Value::Null);
+
+        let reply = call(&mut server, 2, "textDocument/hover", at(1, 5));
+        assert_eq!(reply["error"]["code"], INVALID_REQUEST);
+        assert!(
+            notify(
+                &mut server,
+                "textDocument/didClose",
+                json!({ "textDocument": { "uri": URI } }),
+            )
+            .is_empty()
+        );
+
+        let (code, replies) = send(&mut server, json!({ "jsonrpc": "2.0", "method": "exit" }));
+        assert_eq!(code, Some(0));
+        assert!(replies.is_empty());
+    }
+
+    #[test]
+    fn exit_without_shutdown_fails() {
+        let mut server = Server::default();
+        let (code, _) = send(&mut server, json!({ "jsonrpc": "2.0", "method": "exit" }));
+        assert_eq!(code, Some(1));
+    }
+}
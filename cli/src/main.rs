@@ -1,6 +1,17 @@
-use clap::{Arg, ArgMatches, Command};
-use mainstage_core::ast::generate_ast_from_source;
+mod examples;
+mod explain;
+mod graph;
+mod lsp;
+mod style;
+mod watch;
+
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use mainstage_core::analyzer::{self, CallGraph, LintDiagnostic, LintLevel, LintLevels};
+use mainstage_core::fingerprint::Manifest;
+use mainstage_core::prelude::*;
+use mainstage_core::project::{self, Project};
 use std::fs;
+use std::path::{Path, PathBuf};
 
 fn main() {
     let cli = Command::new("MainStage CLI")
@@ -8,31 +19,74 @@ fn main() {
         .author("Colton McGraw <https://github.com/ColtMcG1>")
         .about("A CLI for MainStage");
 
-    let cli = setup_cli(cli);
+    let user_color = UserConfig::load_default().ok().and_then(|c| c.color);
+    let color = style::configure(
+        style::flag_from_args(std::env::args_os()).as_deref(),
+        user_color.as_deref(),
+    );
+    let cli = setup_cli(cli).color(color);
     let matches = cli.get_matches();
     dispatch_commands(&matches);
 }
 
+/// Stages written by `--dump all`, in pipeline order.
+const DUMP_STAGES: [&str; 2] = ["ast", "analysis"];
+
 /// Sets up the CLI with subcommands and arguments.
 /// This function configures the command-line interface using the `clap` crate.
 /// It defines subcommands for analyzing scripts and generating reports.
 fn setup_cli(cli: Command) -> Command {
-    cli.subcommand(
+    cli.arg(
+        Arg::new("color")
+            .help("When to use colored output; NO_COLOR is honored unless this is given")
+            .long("color")
+            .global(true)
+            .value_parser(mainstage_core::config::user::COLOR_CHOICES)
+            .value_name("WHEN"),
+    )
+    .arg(lint_arg("allow", "Do not report a lint or lint group, e.g. unused"))
+    .arg(lint_arg("warn", "Report a lint or lint group as a warning"))
+    .arg(lint_arg("deny", "Report a lint or lint group as an error and fail the command"))
+    .subcommand(
+        Command::new("analyze")
+            .about("Check a script and report problems without building it")
+            .arg(
+                Arg::new("file")
+                    .help("The script file to analyze; defaults to `script` in mainstage.toml")
+                    .index(1),
+            )
+            .arg(
+                Arg::new("json")
+                    .help("Print the AST and diagnostics as JSON")
+                    .long("json")
+                    .action(ArgAction::SetTrue),
+            ),
+    )
+    .subcommand(
         Command::new("build")
-            .about("Build the specified script file")
+            .about("Build scripts, or a project directory containing mainstage.toml")
             .arg(
                 Arg::new("file")
-                    .help("The script file to build")
-                    .required(true)
+                    .help("Scripts or directories to build; defaults to `script` in mainstage.toml, then the current directory")
+                    .num_args(1..)
                     .index(1),
             )
             .arg(
                 Arg::new("dump")
-                    .help("Specify the dump stage")
+                    .help("Dump stages, comma-separated (ast,analysis,ir,bytecode), or 'all'")
                     .short('d')
                     .long("dump")
                     .value_parser(clap::value_parser!(String))
-                    .value_name("STAGE"),
+                    .value_delimiter(',')
+                    .action(ArgAction::Append)
+                    .value_name("STAGES"),
+            )
+            .arg(
+                Arg::new("dump-dir")
+                    .help("Directory for dump files; defaults to the --output directory")
+                    .long("dump-dir")
+                    .value_parser(clap::value_parser!(PathBuf))
+                    .value_name("DIR"),
             )
             .arg(
                 Arg::new("output")
@@ -41,17 +95,113 @@ fn setup_cli(cli: Command) -> Command {
                     .long("output")
                     .value_parser(clap::value_parser!(String))
                     .value_name("FILE"),
+            )
+            .arg(
+                Arg::new("fingerprint")
+                    .help("Write a manifest of the files written, with their SHA-256 digests")
+                    .long("fingerprint")
+                    .value_parser(clap::value_parser!(PathBuf))
+                    .value_name("MANIFEST"),
+            )
+            .arg(
+                Arg::new("profile")
+                    .help("Apply the named profile from mainstage.toml")
+                    .long("profile")
+                    .value_parser(clap::value_parser!(String))
+                    .value_name("NAME"),
             ),
     )
+    .subcommand(
+        Command::new("config")
+            .about("Read or change the per-user configuration")
+            .subcommand_required(true)
+            .subcommand(
+                Command::new("get")
+                    .about("Print the value of a setting, e.g. compilers.c")
+                    .arg(Arg::new("key").required(true).index(1)),
+            )
+            .subcommand(
+                Command::new("set")
+                    .about("Change a setting and save the configuration")
+                    .arg(Arg::new("key").required(true).index(1))
+                    .arg(Arg::new("value").required(true).index(2)),
+            )
+            .subcommand(Command::new("path").about("Print the location of the configuration file")),
+    )
+    .subcommand(
+        Command::new("examples")
+            .about("List the bundled example projects, or copy one into a directory")
+            .arg(
+                Arg::new("name")
+                    .help("The example to copy; omit to list them")
+                    .index(1),
+            )
+            .arg(
+                Arg::new("dest")
+                    .help("Directory to copy the example into")
+                    .long("dest")
+                    .value_parser(clap::value_parser!(PathBuf))
+                    .default_value(".")
+                    .value_name("DIR"),
+            ),
+    )
+    .subcommand(
+        Command::new("explain-script")
+            .about("Summarize what a script does: entry workspace, stage order, projects and plugins")
+            .arg(
+                Arg::new("file")
+                    .help("The script file to explain; defaults to `script` in mainstage.toml")
+                    .index(1),
+            ),
+    )
+    .subcommand(
+        Command::new("fmt")
+            .about("Format a script file in place")
+            .arg(
+                Arg::new("file")
+                    .help("The script file to format; defaults to `script` in mainstage.toml")
+                    .index(1),
+            )
+            .arg(
+                Arg::new("check")
+                    .help("Only check formatting; exit with status 1 if the file would change")
+                    .long("check")
+                    .action(ArgAction::SetTrue),
+            ),
+    )
+    .subcommand(
+        Command::new("graph")
+            .about("Print the call graph of workspaces, stages and plugins")
+            .arg(
+                Arg::new("file")
+                    .help("The script file to graph; defaults to `script` in mainstage.toml")
+                    .index(1),
+            )
+            .arg(
+                Arg::new("format")
+                    .help("Output format")
+                    .long("format")
+                    .value_parser(graph::FORMATS)
+                    .default_value("dot")
+                    .value_name("FORMAT"),
+            ),
+    )
+    .subcommand(Command::new("lsp").about("Start the language server on stdin/stdout"))
     .subcommand(
         Command::new("run")
             .about("Run a script file")
             .arg(
                 Arg::new("file")
-                    .help("The script file to run")
-                    .required(true)
+                    .help("The script file to run; defaults to `script` in mainstage.toml")
                     .index(1),
             )
+            .arg(
+                Arg::new("watch")
+                    .help("Re-run the script whenever it or an included file changes")
+                    .short('w')
+                    .long("watch")
+                    .action(ArgAction::SetTrue),
+            )
             .arg(
                 Arg::new("dump")
                     .help("Specify the dump stage")
@@ -59,61 +209,567 @@ fn setup_cli(cli: Command) -> Command {
                     .long("dump")
                     .value_parser(clap::value_parser!(String))
                     .value_name("STAGE"),
+            )
+            .arg(
+                Arg::new("profile")
+                    .help("Apply the named profile from mainstage.toml")
+                    .long("profile")
+                    .value_parser(clap::value_parser!(String))
+                    .value_name("NAME"),
+            ),
+    )
+    .subcommand(
+        Command::new("verify")
+            .about("Check that the artifacts in a --fingerprint manifest are unchanged")
+            .arg(
+                Arg::new("manifest")
+                    .help("The manifest written by build --fingerprint")
+                    .required(true)
+                    .value_parser(clap::value_parser!(PathBuf))
+                    .index(1),
             ),
     )
 }
 
+/// A global, repeatable `--<name> LINT[,LINT...]` flag setting lint severity.
+fn lint_arg(name: &'static str, help: &'static str) -> Arg {
+    Arg::new(name)
+        .help(help)
+        .long(name)
+        .global(true)
+        .value_parser(|name: &str| {
+            if analyzer::lints::is_known(name) {
+                Ok(name.to_string())
+            } else {
+                Err(format!("unknown lint '{}'", name))
+            }
+        })
+        .value_delimiter(',')
+        .action(ArgAction::Append)
+        .value_name("LINT")
+}
+
 /// Dispatches the command based on the parsed arguments.
 /// This function matches the subcommand used and calls the appropriate handler.
 fn dispatch_commands(matches: &ArgMatches) {
     match matches.subcommand() {
+        Some(("analyze", sub_m)) => {
+            let file = script_arg(sub_m);
+            let script = load_script(&file);
+
+            let config = load_config(&file, None);
+            let levels = lint_levels(sub_m, &config);
+
+            let result = generate_ast_from_source(&script);
+            let mut denied = false;
+            if sub_m.get_flag("json") {
+                let (ast, diagnostics) = match &result {
+                    Ok(ast) => {
+                        let lints = analyzer::check_with(ast, &levels);
+                        denied = denies(&lints);
+                        (
+                            serde_json::to_value(ast).expect("AST is serializable"),
+                            lints.iter().map(|d| diagnostic_json(d)).collect(),
+                        )
+                    }
+                    Err(e) => (serde_json::Value::Null, vec![diagnostic_json(e.as_ref())]),
+                };
+                let output = serde_json::json!({
+                    "file": file.display().to_string(),
+                    "ast": ast,
+                    "diagnostics": diagnostics,
+                });
+                println!("{}", serde_json::to_string_pretty(&output).expect("valid JSON"));
+            } else {
+                match &result {
+                    Ok(ast) => {
+                        let lints = report_lints(ast, &levels);
+                        denied = denies(&lints);
+                        if lints.is_empty() {
+                            println!("{}: no problems found", file.display());
+                        }
+                    }
+                    Err(e) => println!("{} {}", style::error("Error generating AST:"), e),
+                }
+            }
+            if result.is_err() || denied {
+                std::process::exit(1);
+            }
+        }
         Some(("build", sub_m)) => {
-            let file = sub_m.get_one::<String>("file").expect("required argument");
             let out = sub_m.get_one::<String>("output");
+            let mut roots = Vec::new();
+            for input in build_inputs(sub_m) {
+                match project::resolve_input(&input) {
+                    Ok(scripts) => roots.extend(scripts),
+                    Err(e) => {
+                        println!("{} {}", style::error("Error:"), e);
+                        std::process::exit(1);
+                    }
+                }
+            }
+            let config = load_config(&roots[0], sub_m.get_one::<String>("profile"));
 
-            let script = mainstage_core::script::Script::new(std::path::PathBuf::from(file))
-                .expect("Failed to load script file");
+            let (project, errors) = Project::load(&roots);
+            for error in &errors {
+                println!("{} {}", style::error("Error:"), error);
+            }
+            if !errors.is_empty() {
+                std::process::exit(1);
+            }
 
-            // Properly handle the Result so we don't silently drop errors.
-            let ast = match generate_ast_from_source(&script) {
-                Ok(ast) => ast,
-                Err(e) => {
-                    // Print a helpful message and stop processing this command.
-                    println!("Error generating AST: {}", e);
-                    return;
-                }
-            };
+            let levels = lint_levels(sub_m, &config);
+            let lints: Vec<Vec<LintDiagnostic>> = project
+                .files
+                .iter()
+                .map(|file| report_lints(&file.ast, &levels))
+                .collect();
+            if lints.iter().any(|file_lints| denies(file_lints)) {
+                std::process::exit(1);
+            }
 
+            let mut written = Vec::new();
             if let Some(output_file) = out {
-                fs::write(output_file, format!("{:#?}", ast)).expect("Failed to write output file");
+                let asts: String = project.files.iter().map(|f| format!("{:#?}", f.ast)).collect();
+                fs::write(output_file, asts).expect("Failed to write output file");
+                written.push(PathBuf::from(output_file));
             }
 
-            if let Some(dump_stage) = sub_m.get_one::<String>("dump") {
-                match dump_stage.as_str() {
-                    "ast" => {
-                        fs::write("dumped_ast.txt", format!("{:#?}", ast))
-                            .expect("Failed to write dumped AST");
+            if let Some(requested) = sub_m.get_many::<String>("dump") {
+                let mut stages: Vec<&str> = Vec::new();
+                for stage in requested.map(String::as_str) {
+                    let expanded = if stage == "all" { &DUMP_STAGES[..] } else { &[stage][..] };
+                    for stage in expanded {
+                        if !stages.contains(stage) {
+                            stages.push(stage);
+                        }
                     }
-                    _ => {
-                        println!("Unknown dump stage: {}", dump_stage);
+                }
+                let dump_dir = dump_directory(
+                    sub_m.get_one::<PathBuf>("dump-dir"),
+                    out.map(Path::new),
+                    &config,
+                );
+                for stage in stages {
+                    let contents = match stage {
+                        "ast" => match &project.files[..] {
+                            [file] => mainstage_core::testing::render_ast(&file.ast),
+                            files => files
+                                .iter()
+                                .map(|f| {
+                                    format!(
+                                        "// {}\n{}",
+                                        f.script.path.display(),
+                                        mainstage_core::testing::render_ast(&f.ast)
+                                    )
+                                })
+                                .collect(),
+                        },
+                        "analysis" => {
+                            let files: Vec<serde_json::Value> = project
+                                .files
+                                .iter()
+                                .zip(&lints)
+                                .map(|(file, file_lints)| {
+                                    serde_json::json!({
+                                        "file": file.script.path.display().to_string(),
+                                        "call_graph": CallGraph::from_ast(&file.ast),
+                                        "diagnostics": file_lints
+                                            .iter()
+                                            .map(|d| diagnostic_json(d))
+                                            .collect::<Vec<_>>(),
+                                    })
+                                })
+                                .collect();
+                            serde_json::to_string_pretty(&files).expect("valid JSON")
+                        }
+                        "ir" | "bytecode" => {
+                            println!(
+                                "{} the {} stage is not produced yet; skipping",
+                                style::warning("Cannot dump:"),
+                                stage
+                            );
+                            continue;
+                        }
+                        _ => {
+                            println!("{} {}", style::warning("Unknown dump stage:"), stage);
+                            continue;
+                        }
+                    };
+                    if !dump_dir.as_os_str().is_empty() {
+                        fs::create_dir_all(&dump_dir).expect("Failed to create output directory");
                     }
+                    let dump_file = dump_dir.join(format!("dumped_{}.txt", stage));
+                    fs::write(&dump_file, contents).expect("Failed to write dump file");
+                    written.push(dump_file);
+                }
+            }
+
+            if let Some(manifest_path) = sub_m.get_one::<PathBuf>("fingerprint") {
+                let mut manifest = Manifest::default();
+                let saved = written
+                    .iter()
+                    .try_for_each(|file| manifest.record(file))
+                    .and_then(|_| manifest.save(manifest_path));
+                if let Err(e) = saved {
+                    println!("{} {}", style::error("Error writing fingerprint manifest:"), e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Some(("config", sub_m)) => config_command(sub_m),
+        Some(("examples", sub_m)) => {
+            let Some(name) = sub_m.get_one::<String>("name") else {
+                for example in examples::EXAMPLES {
+                    println!("{:<20} {}", example.name, example.description);
+                }
+                return;
+            };
+            let Some(example) = examples::find(name) else {
+                println!("{} {}", style::error("Unknown example:"), name);
+                std::process::exit(1);
+            };
+            let dest = sub_m.get_one::<PathBuf>("dest").expect("has a default");
+            match examples::copy(example, dest) {
+                Ok(dir) => println!("{} {} into {}", style::success("Copied"), name, dir.display()),
+                Err(e) => {
+                    println!("{} {}", style::error("Error copying example:"), e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Some(("explain-script", sub_m)) => {
+            let file = script_arg(sub_m);
+
+            let (project, errors) = Project::load(&[file]);
+            for error in &errors {
+                println!("{} {}", style::error("Error:"), error);
+            }
+            if !errors.is_empty() {
+                std::process::exit(1);
+            }
+            print!("{}", explain::explain(&project));
+        }
+        Some(("fmt", sub_m)) => {
+            let file = script_arg(sub_m);
+
+            let script = load_script(&file);
+            let formatted = match format_script(&script) {
+                Ok(formatted) => formatted,
+                Err(e) => {
+                    println!("{} {}", style::error("Error formatting script:"), e);
+                    std::process::exit(1);
+                }
+            };
+
+            if formatted == script.content {
+                return;
+            }
+            if sub_m.get_flag("check") {
+                println!("{} is not formatted", style::warning(file.display()));
+                std::process::exit(1);
+            }
+            fs::write(&file, formatted).expect("Failed to write formatted script");
+            println!("{} {}", style::success("Formatted"), file.display());
+        }
+        Some(("graph", sub_m)) => {
+            let file = script_arg(sub_m);
+            let format = sub_m.get_one::<String>("format").expect("has a default");
+
+            let script = load_script(&file);
+            match generate_ast_from_source(&script) {
+                Ok(ast) => print!("{}", graph::render(&CallGraph::from_ast(&ast), format)),
+                Err(e) => {
+                    println!("{} {}", style::error("Error generating AST:"), e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Some(("lsp", _)) => {
+            match lsp::serve() {
+                Ok(0) => {}
+                Ok(code) => std::process::exit(code),
+                Err(e) => {
+                    eprintln!("{} {}", style::error("Language server stopped:"), e);
+                    std::process::exit(1);
                 }
             }
         }
         Some(("run", sub_m)) => {
-            let _file = sub_m.get_one::<String>("file").expect("required argument");
+            let path = script_arg(sub_m);
+            // Loaded up front so a broken config is reported before anything runs.
+            let config = load_config(&path, sub_m.get_one::<String>("profile"));
+            let levels = lint_levels(sub_m, &config);
+            apply_env(&config);
 
             if let Some(dump_stage) = sub_m.get_one::<String>("dump") {
                 match dump_stage.as_str() {
                     "ast" => {}
                     _ => {
-                        println!("Unknown dump stage: {}", dump_stage);
+                        println!("{} {}", style::warning("Unknown dump stage:"), dump_stage);
                     }
                 }
             }
+
+            if sub_m.get_flag("watch") {
+                watch::watch(|| run_script(&path, &levels).0);
+            }
+            let (_, succeeded) = run_script(&path, &levels);
+            if !succeeded {
+                std::process::exit(1);
+            }
+        }
+        Some(("verify", sub_m)) => {
+            let path = sub_m.get_one::<PathBuf>("manifest").expect("required argument");
+            let manifest = match Manifest::load(path) {
+                Ok(manifest) => manifest,
+                Err(e) => {
+                    println!("{} {}: {}", style::error("Error reading manifest"), path.display(), e);
+                    std::process::exit(1);
+                }
+            };
+            let mismatches = manifest.verify();
+            for mismatch in &mismatches {
+                println!("{} {}", style::error("Mismatch:"), mismatch);
+            }
+            if !mismatches.is_empty() {
+                std::process::exit(1);
+            }
+            println!("{} {} artifacts", style::success("Verified"), manifest.artifacts.len());
         }
         _ => {
             println!("No valid subcommand was used. Use --help for more information.");
         }
     }
 }
+
+/// Prints the analyzer's findings for a script and returns them.
+fn report_lints(ast: &AstNode, levels: &LintLevels) -> Vec<LintDiagnostic> {
+    let lints = analyzer::check_with(ast, levels);
+    for lint in &lints {
+        let lint: &dyn MainstageErrorExt = lint;
+        let label = match lint.level() {
+            Level::Error => style::error("Error:"),
+            _ => style::warning("Warning:"),
+        };
+        println!("{} {}", label, lint);
+    }
+    lints
+}
+
+/// Whether any finding was raised to an error by `--deny` or `[lints]`.
+fn denies(lints: &[LintDiagnostic]) -> bool {
+    lints.iter().any(|d| d.level() == Level::Error)
+}
+
+/// Lint levels from the configuration's `[lints]` table, overridden by `--allow`, `--warn`
+/// and `--deny` in the order they were given on the command line.
+fn lint_levels(matches: &ArgMatches, config: &Config) -> LintLevels {
+    let mut levels = LintLevels::default();
+    levels.apply(&config.lints);
+    let mut flags = Vec::new();
+    for (flag, level) in [
+        ("allow", LintLevel::Allow),
+        ("warn", LintLevel::Warn),
+        ("deny", LintLevel::Deny),
+    ] {
+        if let (Some(names), Some(indices)) =
+            (matches.get_many::<String>(flag), matches.indices_of(flag))
+        {
+            flags.extend(indices.zip(names).map(|(index, name)| (index, name, level)));
+        }
+    }
+    flags.sort_by_key(|(index, ..)| *index);
+    for (_, name, level) in flags {
+        levels.set(name.clone(), level);
+    }
+    levels
+}
+
+/// Describes an error for `--json` output.
+fn diagnostic_json(error: &dyn MainstageErrorExt) -> serde_json::Value {
+    serde_json::json!({
+        "level": error.level().to_string(),
+        "message": error.message(),
+        "issuer": error.issuer(),
+        "location": error.location(),
+        "span": error.span(),
+    })
+}
+
+/// Picks where dump files go: `--dump-dir`, then the directory of `--output`, then the
+/// configured `out_dir`, and finally the current directory.
+fn dump_directory(dump_dir: Option<&PathBuf>, output: Option<&Path>, config: &Config) -> PathBuf {
+    if let Some(dir) = dump_dir {
+        return dir.clone();
+    }
+    if let Some(parent) = output.and_then(Path::parent) {
+        return parent.to_path_buf();
+    }
+    config.out_dir.clone().unwrap_or_default()
+}
+
+/// Loads the `mainstage.toml` governing the current directory, if there is one. Only
+/// needed when no script was named, so a broken file does not get in the way of commands
+/// that were given one. Exits on an invalid configuration.
+fn load_project_config() -> Option<Config> {
+    let cwd = std::env::current_dir().unwrap_or_default();
+    Config::discover(&cwd).unwrap_or_else(|e| {
+        println!("{} {}", style::error("Error loading configuration:"), e);
+        std::process::exit(1);
+    })
+}
+
+/// The script named on the command line, or else the project's configured `script`.
+fn script_arg(matches: &ArgMatches) -> PathBuf {
+    if let Some(file) = matches.get_one::<String>("file") {
+        return PathBuf::from(file);
+    }
+    match load_project_config().and_then(|config| config.script) {
+        Some(script) => script,
+        None => {
+            println!(
+                "{} no script given and no `script` set in {}",
+                style::error("Error:"),
+                mainstage_core::config::CONFIG_FILE_NAME
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Reads the script at `path`, exiting with an error if it cannot be read.
+fn load_script(path: &Path) -> Script {
+    match Script::new(path.to_path_buf()) {
+        Ok(script) => script,
+        Err(e) => {
+            println!("{} {}", style::error("Error loading script:"), e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// The inputs to `build`: the paths on the command line, else the project's `script`, else
+/// the current directory.
+fn build_inputs(matches: &ArgMatches) -> Vec<PathBuf> {
+    if let Some(files) = matches.get_many::<String>("file") {
+        return files.map(PathBuf::from).collect();
+    }
+    match load_project_config().and_then(|config| config.script) {
+        Some(script) => vec![script],
+        None => vec![PathBuf::from(".")],
+    }
+}
+
+/// Sets the configured environment variables for the scripts about to run.
+fn apply_env(config: &Config) {
+    for (key, value) in &config.env {
+        // SAFETY: called on the main thread before any script runs or threads are spawned.
+        unsafe { std::env::set_var(key, value) };
+    }
+}
+
+/// Loads the `mainstage.toml` governing `script`, searching upward from its directory,
+/// and applies `profile` when one was requested. Exits on an invalid configuration.
+fn load_config(script: &Path, profile: Option<&String>) -> Config {
+    let dir = script
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    let start = std::path::absolute(dir).unwrap_or_else(|_| dir.to_path_buf());
+    let loaded = Config::discover(&start).and_then(|config| {
+        let config = config.unwrap_or_default();
+        let config = match profile {
+            Some(name) => config.with_profile(name)?,
+            None => config,
+        };
+        Ok(config.with_user_defaults(&UserConfig::load_default()?))
+    });
+    loaded.unwrap_or_else(|e| {
+        println!("{} {}", style::error("Error loading configuration:"), e);
+        std::process::exit(1);
+    })
+}
+
+/// Handles `mainstage config get|set|path` against the per-user configuration file.
+fn config_command(matches: &ArgMatches) {
+    let mut config = match UserConfig::load_default() {
+        Ok(config) => config,
+        Err(e) => {
+            println!("{} {}", style::error("Error loading configuration:"), e);
+            std::process::exit(1);
+        }
+    };
+    match matches.subcommand() {
+        Some(("get", sub_m)) => {
+            let key = sub_m.get_one::<String>("key").expect("required argument");
+            match config.get(key) {
+                Some(value) => println!("{}", value),
+                None => {
+                    println!("{} is not set", key);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Some(("set", sub_m)) => {
+            let key = sub_m.get_one::<String>("key").expect("required argument");
+            let value = sub_m.get_one::<String>("value").expect("required argument");
+            if let Err(e) = config.set(key, value).and_then(|_| config.save()) {
+                println!("{} {}", style::error("Error updating configuration:"), e);
+                std::process::exit(1);
+            }
+        }
+        Some(("path", _)) => match UserConfig::default_path() {
+            Some(path) => println!("{}", path.display()),
+            None => {
+                println!("No home directory found for the user configuration.");
+                std::process::exit(1);
+            }
+        },
+        _ => unreachable!("a config subcommand is required"),
+    }
+}
+
+/// Compiles and runs a script, reporting errors to the console. Returns every file the run depended on so that watch mode knows what to poll, and
+/// whether the run succeeded. Load, parse, compile and runtime errors fail the run, as
+/// does a denied lint.
+fn run_script(path: &Path, levels: &LintLevels) -> (Vec<PathBuf>, bool) {
+    let mut files = vec![path.to_path_buf()];
+
+    let (project, errors) = Project::load(&[path.to_path_buf()]);
+    for file in &project.files {
+        if !files.contains(&file.script.path) {
+            files.push(file.script.path.clone());
+        }
+    }
+    for error in &errors {
+        println!("{} {}", style::error("Error:"), error);
+    }
+    if !errors.is_empty() {
+        return (files, false);
+    }
+
+    let mut denied = false;
+    for file in &project.files {
+        denied |= denies(&report_lints(&file.ast, levels));
+    }
+    if denied {
+        return (files, false);
+    }
+    let script = &project.files[0].script;
+
+    let ir = match mainstage_core::compile_source_to_ir(script) {
+        Ok(ir) => ir,
+        Err(e) => {
+            println!("{} {}", style::error("Error compiling script:"), e);
+            return (files, false);
+        }
+    };
+
+    if let Err(e) = mainstage_core::run_ir_in_vm(&ir) {
+        println!("{} {}", style::error("Error running script:"), e);
+        return (files, false);
+    }
+    (files, true)
+}
+
@@ -0,0 +1,97 @@
+use std::ffi::OsString;
+
+use clap::ColorChoice;
+use console::{StyledObject, style};
+use mainstage_core::config::user::COLOR_CHOICES;
+
+/// Decides whether output is colored, in order of precedence: the `--color` flag, the
+/// `NO_COLOR` environment variable, the `color` user setting, and finally terminal
+/// detection (which also honors `CLICOLOR`/`CLICOLOR_FORCE`). Returns the matching
+/// setting for clap so that its help and usage errors follow the same choice.
+pub fn configure(flag: Option<&str>, user_setting: Option<&str>) -> ColorChoice {
+    let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    let choice = match (flag, no_color) {
+        (Some(choice), _) => choice,
+        (None, true) => "never",
+        (None, false) => user_setting.unwrap_or("auto"),
+    };
+    let enabled = match choice {
+        "always" => true,
+        "never" => false,
+        _ => return ColorChoice::Auto,
+    };
+    console::set_colors_enabled(enabled);
+    console::set_colors_enabled_stderr(enabled);
+    if enabled {
+        ColorChoice::Always
+    } else {
+        ColorChoice::Never
+    }
+}
+
+/// Finds `--color` in the raw arguments. Coloring has to be decided before clap parses
+/// them, since clap prints help and usage errors while parsing; an invalid value is left
+/// for clap to report.
+pub fn flag_from_args(args: impl IntoIterator<Item = OsString>) -> Option<String> {
+    let mut args = args.into_iter().skip(1);
+    let mut flag = None;
+    while let Some(arg) = args.next() {
+        let value = match arg.to_str() {
+            Some("--") => break,
+            Some("--color") => args.next(),
+            Some(arg) => arg.strip_prefix("--color=").map(OsString::from),
+            None => None,
+        };
+        if let Some(value) = value.as_ref().and_then(|v| v.to_str()) {
+            flag = Some(value.to_string());
+        }
+    }
+    flag.filter(|value| COLOR_CHOICES.contains(&value.as_str()))
+}
+
+pub fn error<D>(text: D) -> StyledObject<D> {
+    style(text).red().bold()
+}
+
+pub fn warning<D>(text: D) -> StyledObject<D> {
+    style(text).yellow().bold()
+}
+
+pub fn success<D>(text: D) -> StyledObject<D> {
+    style(text).green()
+}
+
+pub fn note<D>(text: D) -> StyledObject<D> {
+    style(text).cyan()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flag(args: &[&str]) -> Option<String> {
+        flag_from_args(
+            std::iter::once("mainstage")
+                .chain(args.iter().copied())
+                .map(OsString::from),
+        )
+    }
+
+    #[test]
+    fn color_flag_is_found_before_parsing() {
+        assert_eq!(flag(&["--color", "never", "run"]).as_deref(), Some("never"));
+        assert_eq!(flag(&["run", "--color=always"]).as_deref(), Some("always"));
+        assert_eq!(
+            flag(&["--color=never", "run", "--color", "always"]).as_deref(),
+            Some("always")
+        );
+        assert_eq!(flag(&["run", "build.ms"]), None);
+    }
+
+    #[test]
+    fn invalid_or_escaped_values_are_left_to_clap() {
+        assert_eq!(flag(&["--color", "sometimes"]), None);
+        assert_eq!(flag(&["--color"]), None);
+        assert_eq!(flag(&["run", "--", "--color=always"]), None);
+    }
+}
@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use crate::style;
+
+/// How often watched files are polled for modification.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+/// How long the watched files must stay unchanged before re-running.
+const DEBOUNCE: Duration = Duration::from_millis(150);
+/// Allowance for modification times that lag the system clock: many file systems stamp
+/// files from a clock that only advances once per tick, so a file saved just after a run
+/// started can carry an earlier time than the run.
+const MTIME_RESOLUTION: Duration = Duration::from_millis(100);
+
+type Snapshot = HashMap<PathBuf, Option<SystemTime>>;
+
+/// Runs `run` and then re-runs it every time one of the files it reported changes.
+/// Each call of `run` returns the files it depended on; they are added to the watched
+/// set so newly included files are picked up without restarting, and files are kept
+/// watched even when a broken edit stops the script from parsing. Never returns; the
+/// loop is stopped with Ctrl-C.
+pub fn watch<F>(mut run: F) -> !
+where
+    F: FnMut() -> Vec<PathBuf>,
+{
+    let mut iteration = 1usize;
+    let mut files: Vec<PathBuf> = Vec::new();
+    loop {
+        let changed = run_until_change(&mut run, &mut files);
+
+        iteration += 1;
+        println!();
+        println!(
+            "{} {} changed, re-running (run #{})",
+            style::note("[watch]"),
+            changed.display(),
+            iteration
+        );
+    }
+}
+
+/// One iteration of [`watch`]: runs `run`, adds the files it reports to `files`, then
+/// waits for one of them to change and for the edits to settle. Returns the changed file.
+fn run_until_change<F>(run: &mut F, files: &mut Vec<PathBuf>) -> PathBuf
+where
+    F: FnMut() -> Vec<PathBuf>,
+{
+    // Taken before running so that edits saved while the script runs trigger a re-run.
+    // Files reported for the first time have no earlier snapshot; one modified after
+    // the run started, give or take the timestamp resolution, is recorded as unseen so
+    // that it counts as changed.
+    let started = SystemTime::now() - MTIME_RESOLUTION;
+    let mut watched = snapshot(files);
+    for file in run() {
+        if !files.contains(&file) {
+            let time = modified(&file).filter(|time| *time < started);
+            watched.insert(file.clone(), time);
+            files.push(file);
+        }
+    }
+    println!(
+        "{} Watching {} file(s) for changes. Press Ctrl-C to stop.",
+        style::note("[watch]"),
+        watched.len()
+    );
+
+    let changed = wait_for_change(&watched);
+    settle(files);
+    changed
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn snapshot(files: &[PathBuf]) -> Snapshot {
+    files.iter().map(|f| (f.clone(), modified(f))).collect()
+}
+
+/// Blocks until any watched file's modification time differs from the snapshot.
+fn wait_for_change(watched: &Snapshot) -> PathBuf {
+    loop {
+        thread::sleep(POLL_INTERVAL);
+        if let Some((path, _)) = watched.iter().find(|(path, time)| modified(path) != **time) {
+            return path.clone();
+        }
+    }
+}
+
+/// Waits until the files stop changing so that editors writing in several steps
+/// only trigger a single re-run.
+fn settle(files: &[PathBuf]) {
+    let mut last = snapshot(files);
+    loop {
+        thread::sleep(DEBOUNCE);
+        let current = snapshot(files);
+        if current == last {
+            return;
+        }
+        last = current;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::time::Instant;
+
+    /// A fresh directory under the system temp dir, unique to this test process.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("mainstage-watch-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("temp dir is writable");
+        dir
+    }
+
+    /// Writes `path` with a modification time well before any run in the test.
+    fn write_old(path: &Path, contents: &str) {
+        fs::write(path, contents).unwrap();
+        let file = fs::File::options().write(true).open(path).unwrap();
+        file.set_modified(SystemTime::now() - Duration::from_secs(60))
+            .unwrap();
+    }
+
+    /// Writes `path` after `delay` on another thread.
+    fn write_later(path: &Path, delay: Duration, contents: &'static str) -> thread::JoinHandle<()> {
+        let path = path.to_path_buf();
+        thread::spawn(move || {
+            thread::sleep(delay);
+            fs::write(path, contents).unwrap();
+        })
+    }
+
+    #[test]
+    fn edit_after_the_run_is_reported() {
+        let dir = temp_dir("edit");
+        let script = dir.join("main.ms");
+        write_old(&script, "one");
+        let mut files = Vec::new();
+        let mut runs = 0;
+
+        let writer = write_later(&script, POLL_INTERVAL * 2, "two");
+        let changed = run_until_change(
+            &mut || {
+                runs += 1;
+                vec![script.clone()]
+            },
+            &mut files,
+        );
+        writer.join().unwrap();
+        assert_eq!(changed, script);
+        assert_eq!(runs, 1);
+        assert_eq!(files, [script]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn edit_during_the_run_is_reported() {
+        let dir = temp_dir("during");
+        let include = dir.join("lib.ms");
+        // The first run sees the included file for the first time, after it was saved.
+        let changed = run_until_change(
+            &mut || {
+                fs::write(&include, "saved while running").unwrap();
+                vec![include.clone()]
+            },
+            &mut Vec::new(),
+        );
+        assert_eq!(changed, include);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn deleted_file_counts_as_changed() {
+        let dir = temp_dir("deleted");
+        let script = dir.join("main.ms");
+        fs::write(&script, "one").unwrap();
+        let watched = snapshot(std::slice::from_ref(&script));
+        fs::remove_file(&script).unwrap();
+        assert_eq!(wait_for_change(&watched), script);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn settle_waits_for_a_burst_of_writes_to_end() {
+        let dir = temp_dir("settle");
+        let script = dir.join("main.ms");
+        fs::write(&script, "0").unwrap();
+
+        let start = Instant::now();
+        let step = DEBOUNCE / 3;
+        let writer = thread::spawn({
+            let script = script.clone();
+            move || {
+                for contents in ["1", "2", "3", "4"] {
+                    thread::sleep(step);
+                    fs::write(&script, contents).unwrap();
+                }
+            }
+        });
+        settle(std::slice::from_ref(&script));
+        writer.join().unwrap();
+        assert!(start.elapsed() >= step * 4 + DEBOUNCE);
+        assert_eq!(fs::read_to_string(&script).unwrap(), "4");
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
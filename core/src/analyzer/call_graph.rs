@@ -0,0 +1,141 @@
+use serde::Serialize;
+
+use crate::ast::{AstNode, AstNodeKind};
+use crate::location::Span;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphNodeKind {
+    Workspace,
+    Stage,
+    /// An imported plugin, named by its alias.
+    Plugin,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GraphNode {
+    /// Unique across kinds, e.g. `stage:build`.
+    pub id: String,
+    pub name: String,
+    pub kind: GraphNodeKind,
+    pub span: Option<Span>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CallEdge {
+    pub from: String,
+    pub to: String,
+    /// Plugin function called, for edges into a plugin node.
+    pub function: Option<String>,
+    /// The first call site for this edge.
+    pub span: Option<Span>,
+}
+
+/// Which workspaces and stages call which stages and plugin functions. Nodes and edges are
+/// in source order, and each caller/callee/function combination appears once.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CallGraph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<CallEdge>,
+}
+
+impl CallGraph {
+    /// Builds the graph from a script's top-level declarations. Calls to names that are
+    /// neither stages nor `alias.function` on an import are not included.
+    pub fn from_ast(script: &AstNode) -> CallGraph {
+        let mut graph = CallGraph::default();
+        let items = script.children();
+
+        for item in &items {
+            let (kind, name) = match item.get_kind() {
+                AstNodeKind::Workspace { name, .. } => (GraphNodeKind::Workspace, name),
+                AstNodeKind::Stage { name, .. } => (GraphNodeKind::Stage, name),
+                AstNodeKind::Import { alias, .. } => (GraphNodeKind::Plugin, alias),
+                _ => continue,
+            };
+            graph.nodes.push(GraphNode {
+                id: node_id(kind, name),
+                name: name.clone(),
+                kind,
+                span: item.get_span().cloned(),
+            });
+        }
+
+        for item in &items {
+            let (from, body) = match item.get_kind() {
+                AstNodeKind::Workspace { name, body, .. } => {
+                    (node_id(GraphNodeKind::Workspace, name), body)
+                }
+                AstNodeKind::Stage { name, body, .. } => {
+                    (node_id(GraphNodeKind::Stage, name), body)
+                }
+                _ => continue,
+            };
+            graph.collect_calls(&from, body);
+        }
+        graph
+    }
+
+    /// Returns the node with the given id.
+    pub fn node(&self, id: &str) -> Option<&GraphNode> {
+        self.nodes.iter().find(|n| n.id == id)
+    }
+
+    /// Returns the ids of every node reachable from `start`, including `start` itself.
+    pub fn reachable_from<'a>(&'a self, start: &'a str) -> Vec<&'a str> {
+        let mut seen = vec![start];
+        let mut next = 0;
+        while let Some(&current) = seen.get(next) {
+            next += 1;
+            for edge in self.edges.iter().filter(|e| e.from == current) {
+                if !seen.contains(&edge.to.as_str()) {
+                    seen.push(&edge.to);
+                }
+            }
+        }
+        seen
+    }
+
+    fn collect_calls(&mut self, from: &str, node: &AstNode) {
+        for child in node.children() {
+            self.collect_calls(from, child);
+        }
+        let AstNodeKind::Call { callee, .. } = node.get_kind() else {
+            return;
+        };
+        let (to, function) = match callee.get_kind() {
+            AstNodeKind::Identifier { name } => (node_id(GraphNodeKind::Stage, name), None),
+            AstNodeKind::Member { object, member } => match object.get_kind() {
+                AstNodeKind::Identifier { name } => {
+                    (node_id(GraphNodeKind::Plugin, name), Some(member.clone()))
+                }
+                _ => return,
+            },
+            _ => return,
+        };
+        if self.node(&to).is_none() {
+            return;
+        }
+        let duplicate = self
+            .edges
+            .iter()
+            .any(|e| e.from == from && e.to == to && e.function == function);
+        if !duplicate {
+            self.edges.push(CallEdge {
+                from: from.to_string(),
+                to,
+                function,
+                span: node.get_span().cloned(),
+            });
+        }
+    }
+}
+
+fn node_id(kind: GraphNodeKind, name: &str) -> String {
+    let prefix = match kind {
+        GraphNodeKind::Workspace => "workspace",
+        GraphNodeKind::Stage => "stage",
+        GraphNodeKind::Plugin => "plugin",
+    };
+    format!("{}:{}", prefix, name)
+}
@@ -0,0 +1,28 @@
+use crate::ast::{AstNode, AstNodeKind};
+
+/// Attribute that marks the declaration a script starts from.
+pub const ENTRYPOINT_ATTRIBUTE: &str = "entrypoint";
+
+/// Returns the declaration a script starts from: the first workspace, project or stage
+/// marked `[entrypoint]`, or else the first workspace. Scripts with neither are libraries
+/// and have no entry point.
+pub fn entry_point(script: &AstNode) -> Option<&AstNode> {
+    let items = script.children();
+    items
+        .iter()
+        .copied()
+        .find(|item| match item.get_kind() {
+            AstNodeKind::Workspace { attributes, .. }
+            | AstNodeKind::Project { attributes, .. }
+            | AstNodeKind::Stage { attributes, .. } => {
+                attributes.iter().any(|a| a == ENTRYPOINT_ATTRIBUTE)
+            }
+            _ => false,
+        })
+        .or_else(|| {
+            items
+                .iter()
+                .copied()
+                .find(|item| matches!(item.get_kind(), AstNodeKind::Workspace { .. }))
+        })
+}
@@ -0,0 +1,347 @@
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+
+use crate::ast::{AstNode, AstNodeKind};
+use crate::error::{Level, MainstageErrorExt};
+use crate::location::{Location, Span};
+
+use super::call_graph::CallGraph;
+use super::entry::entry_point;
+
+/// Names of the lints reported by [`check`].
+pub const UNUSED_STAGE: &str = "unused_stage";
+pub const UNUSED_VARIABLE: &str = "unused_variable";
+pub const UNREACHABLE_CODE: &str = "unreachable_code";
+/// An `@allow(...)` naming something that is neither a lint nor a group.
+pub const UNKNOWN_LINT: &str = "unknown_lint";
+
+pub const LINTS: [&str; 4] = [
+    UNUSED_STAGE,
+    UNUSED_VARIABLE,
+    UNREACHABLE_CODE,
+    UNKNOWN_LINT,
+];
+
+/// Group names usable anywhere a lint name is, each standing for the lints listed with it.
+pub const GROUPS: [(&str, &[&str]); 2] = [
+    ("unused", &[UNUSED_STAGE, UNUSED_VARIABLE]),
+    ("all", &LINTS),
+];
+
+/// Whether `name` is a lint or a group.
+pub fn is_known(name: &str) -> bool {
+    LINTS.contains(&name) || GROUPS.iter().any(|(group, _)| *group == name)
+}
+
+/// Whether `name`, a lint or group, covers `lint`.
+fn covers(name: &str, lint: &str) -> bool {
+    name == lint
+        || GROUPS
+            .iter()
+            .any(|(group, lints)| *group == name && lints.contains(&lint))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintLevel {
+    /// The lint is not reported.
+    Allow,
+    /// Reported at `Level::Warning`.
+    #[default]
+    Warn,
+    /// Reported at `Level::Error`, which fails `build` and `run`.
+    Deny,
+}
+
+/// Severity for each lint. Settings are applied in order, so a later one overrides an
+/// earlier one that covers the same lint; lints without a setting warn.
+#[derive(Debug, Clone, Default)]
+pub struct LintLevels {
+    settings: Vec<(String, LintLevel)>,
+}
+
+impl LintLevels {
+    pub fn set(&mut self, name: impl Into<String>, level: LintLevel) {
+        self.settings.push((name.into(), level));
+    }
+
+    /// Applies a `[lints]` table, groups first so that individual lints in the same table
+    /// take precedence over them.
+    pub fn apply(&mut self, table: &BTreeMap<String, LintLevel>) {
+        let (groups, lints): (Vec<_>, Vec<_>) = table
+            .iter()
+            .partition(|(name, _)| GROUPS.iter().any(|(group, _)| group == name));
+        for (name, level) in groups.into_iter().chain(lints) {
+            self.set(name.clone(), *level);
+        }
+    }
+
+    pub fn level(&self, lint: &str) -> LintLevel {
+        self.settings
+            .iter()
+            .rev()
+            .find(|(name, _)| covers(name, lint))
+            .map(|(_, level)| *level)
+            .unwrap_or_default()
+    }
+}
+
+/// A problem found by the analyzer. Lints are reported at `Level::Warning` unless denied.
+#[derive(Debug, Clone)]
+pub struct LintDiagnostic {
+    pub lint: &'static str,
+    level: Level,
+    message: String,
+    location: Option<Location>,
+    span: Option<Span>,
+}
+
+impl LintDiagnostic {
+    fn new(lint: &'static str, message: String, node: &AstNode) -> Self {
+        LintDiagnostic {
+            lint,
+            level: Level::Warning,
+            message,
+            location: node.location.clone(),
+            span: node.span.clone(),
+        }
+    }
+}
+
+impl std::fmt::Display for LintDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for LintDiagnostic {}
+
+impl MainstageErrorExt for LintDiagnostic {
+    fn level(&self) -> Level {
+        self.level
+    }
+
+    fn message(&self) -> String {
+        self.message.clone()
+    }
+
+    fn issuer(&self) -> String {
+        format!("mainstage.analyzer.{}", self.lint)
+    }
+
+    fn span(&self) -> Option<Span> {
+        self.span.clone()
+    }
+
+    fn location(&self) -> Option<Location> {
+        self.location.clone()
+    }
+}
+
+/// Runs every lint over a parsed script at the default levels.
+pub fn check(script: &AstNode) -> Vec<LintDiagnostic> {
+    check_with(script, &LintLevels::default())
+}
+
+/// Runs every lint over a parsed script and returns the findings in source order. Findings
+/// inside a statement or declaration marked `@allow(<lint or group>)` are dropped, as are
+/// allowed lints; denied lints are raised to `Level::Error`.
+pub fn check_with(script: &AstNode, levels: &LintLevels) -> Vec<LintDiagnostic> {
+    let mut diagnostics = Vec::new();
+    unused_stages(script, &mut diagnostics);
+    unused_variables(script, &mut diagnostics);
+    unreachable_code(script, &mut diagnostics);
+    unknown_lints(script, &mut diagnostics);
+
+    let mut suppressions = Vec::new();
+    collect_suppressions(script, &mut suppressions);
+    diagnostics.retain_mut(|d| {
+        if suppressions
+            .iter()
+            .any(|(span, allow)| allow.iter().any(|a| covers(a, d.lint)) && contains(span, &d.span))
+        {
+            return false;
+        }
+        match levels.level(d.lint) {
+            LintLevel::Allow => false,
+            LintLevel::Warn => true,
+            LintLevel::Deny => {
+                d.level = Level::Error;
+                true
+            }
+        }
+    });
+    diagnostics.sort_by_key(|d| d.location.as_ref().map(|l| (l.line, l.column)));
+    diagnostics
+}
+
+fn collect_suppressions<'a>(node: &'a AstNode, out: &mut Vec<(&'a Span, &'a [String])>) {
+    if let Some(span) = node.get_span()
+        && !node.allow.is_empty()
+    {
+        out.push((span, &node.allow));
+    }
+    for child in node.children() {
+        collect_suppressions(child, out);
+    }
+}
+
+fn contains(outer: &Span, inner: &Option<Span>) -> bool {
+    let Some(inner) = inner else {
+        return false;
+    };
+    let position = |l: &Location| (l.line, l.column);
+    position(&outer.start) <= position(&inner.start) && position(&inner.end) <= position(&outer.end)
+}
+
+/// `@allow` names that match no lint or group, which would otherwise silently do nothing.
+fn unknown_lints(node: &AstNode, diagnostics: &mut Vec<LintDiagnostic>) {
+    for name in node.allow.iter().filter(|name| !is_known(name)) {
+        diagnostics.push(LintDiagnostic::new(
+            UNKNOWN_LINT,
+            format!("Unknown lint '{}' in @allow.", name),
+            node,
+        ));
+    }
+    for child in node.children() {
+        unknown_lints(child, diagnostics);
+    }
+}
+
+/// Stages that cannot be reached from the script's [`entry_point`]. Scripts without one
+/// are treated as libraries and not checked.
+fn unused_stages(script: &AstNode, diagnostics: &mut Vec<LintDiagnostic>) {
+    let Some(entry) = entry_point(script) else {
+        return;
+    };
+    let (kind, entry_name, body) = match entry.get_kind() {
+        AstNodeKind::Workspace { name, body, .. } => ("workspace", name, body),
+        AstNodeKind::Project { name, body, .. } => ("project", name, body),
+        AstNodeKind::Stage { name, body, .. } => ("stage", name, body),
+        _ => return,
+    };
+    let mut roots = Vec::new();
+    if kind == "stage" {
+        roots.push(entry_name.as_str());
+    }
+    collect_callees(body, &mut roots);
+
+    let graph = CallGraph::from_ast(script);
+    let mut reachable: Vec<String> = Vec::new();
+    for root in roots {
+        let id = format!("stage:{}", root);
+        reachable.extend(graph.reachable_from(&id).into_iter().map(str::to_string));
+    }
+    for item in script.children() {
+        if let AstNodeKind::Stage { name, .. } = item.get_kind()
+            && !reachable.contains(&format!("stage:{}", name))
+        {
+            diagnostics.push(LintDiagnostic::new(
+                UNUSED_STAGE,
+                format!(
+                    "Stage '{}' is never called from {} '{}'.",
+                    name, kind, entry_name
+                ),
+                item,
+            ));
+        }
+    }
+}
+
+/// Names called directly anywhere below `node`, e.g. `build` for `build(app)`.
+fn collect_callees<'a>(node: &'a AstNode, out: &mut Vec<&'a str>) {
+    if let AstNodeKind::Call { callee, .. } = node.get_kind()
+        && let AstNodeKind::Identifier { name } = callee.get_kind()
+    {
+        out.push(name);
+    }
+    for child in node.children() {
+        collect_callees(child, out);
+    }
+}
+
+/// Variables assigned in a workspace or stage that are never read anywhere in the script.
+/// Project assignments are skipped since they are read as members from other declarations.
+fn unused_variables(script: &AstNode, diagnostics: &mut Vec<LintDiagnostic>) {
+    let mut reads = Vec::new();
+    collect_reads(script, &mut reads);
+
+    for item in script.children() {
+        let body = match item.get_kind() {
+            AstNodeKind::Workspace { body, .. } | AstNodeKind::Stage { body, .. } => body,
+            _ => continue,
+        };
+        let mut assignments = Vec::new();
+        collect_assignments(body, &mut assignments);
+        let mut reported: Vec<&str> = Vec::new();
+        for (name, node) in assignments {
+            if reads.contains(&name) || reported.contains(&name) || name.starts_with('_') {
+                continue;
+            }
+            reported.push(name);
+            diagnostics.push(LintDiagnostic::new(
+                UNUSED_VARIABLE,
+                format!("Variable '{}' is assigned but never read.", name),
+                node,
+            ));
+        }
+    }
+}
+
+fn collect_assignments<'a>(node: &'a AstNode, out: &mut Vec<(&'a str, &'a AstNode)>) {
+    if let AstNodeKind::Assignment { target, .. } = node.get_kind()
+        && let AstNodeKind::Identifier { name } = target.get_kind()
+    {
+        out.push((name, node));
+    }
+    for child in node.children() {
+        collect_assignments(child, out);
+    }
+}
+
+/// Collects every identifier read, i.e. every identifier that is not an assignment target.
+fn collect_reads<'a>(node: &'a AstNode, out: &mut Vec<&'a str>) {
+    match node.get_kind() {
+        AstNodeKind::Identifier { name } => out.push(name),
+        AstNodeKind::Assignment { target, value } => {
+            if !matches!(target.get_kind(), AstNodeKind::Identifier { .. }) {
+                collect_reads(target, out);
+            }
+            collect_reads(value, out);
+        }
+        _ => {
+            for child in node.children() {
+                collect_reads(child, out);
+            }
+        }
+    }
+}
+
+/// Statements that follow one which always returns, reported once per block.
+fn unreachable_code(node: &AstNode, diagnostics: &mut Vec<LintDiagnostic>) {
+    if let AstNodeKind::Block { statements } = node.get_kind()
+        && let Some(position) = statements.iter().position(always_returns)
+        && let Some(first_dead) = statements.get(position + 1)
+    {
+        diagnostics.push(LintDiagnostic::new(
+            UNREACHABLE_CODE,
+            "Unreachable statement: the code before it always returns.".into(),
+            first_dead,
+        ));
+    }
+    for child in node.children() {
+        unreachable_code(child, diagnostics);
+    }
+}
+
+fn always_returns(statement: &AstNode) -> bool {
+    match statement.get_kind() {
+        AstNodeKind::Return { .. } => true,
+        AstNodeKind::Block { statements } => statements.iter().any(always_returns),
+        AstNodeKind::IfElse {
+            if_body, else_body, ..
+        } => always_returns(if_body) && always_returns(else_body),
+        _ => false,
+    }
+}
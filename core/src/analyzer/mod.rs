@@ -0,0 +1,9 @@
+//! Semantic analysis over a parsed script.
+
+mod call_graph;
+mod entry;
+pub mod lints;
+
+pub use call_graph::{CallEdge, CallGraph, GraphNode, GraphNodeKind};
+pub use entry::{ENTRYPOINT_ATTRIBUTE, entry_point};
+pub use lints::{LintDiagnostic, LintLevel, LintLevels, check, check_with};
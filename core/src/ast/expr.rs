@@ -17,7 +17,7 @@ pub(crate) fn parse_expression_rule(
         _ => Err(Box::<dyn MainstageErrorExt>::from(Box::new(
             crate::ast::err::SyntaxError::with(
                 crate::Level::Error,
-                format!("Unexpected expression type. {:?}", eq_pair.as_rule()).into(),
+                format!("Unexpected expression type. {:?}", eq_pair.as_rule()),
                 "mainstage.expr.parse_expression_rule".into(),
                 location,
                 span,
@@ -200,54 +200,148 @@ fn parse_unary_expression_rule(
     pair: pest::iterators::Pair<Rule>,
     script: &script::Script,
 ) -> Result<AstNode, Box<dyn MainstageErrorExt>> {
-    let (mut inner_pair, location, span) = rules::get_data_from_rule(&pair, script);
-    let next_rule = rules::fetch_next_pair(&mut inner_pair, &location, &span)?;
-    match next_rule.as_rule() {
-        Rule::unary_op => {
-            let mut inner_pairs = next_rule.into_inner();
-            let op_pair = inner_pairs.next().unwrap();
-            let expr_pair = inner_pairs.next().unwrap();
-
-            Ok(AstNode::new(
-                AstNodeKind::UnaryOp {
-                    op: op_pair.as_str().to_string(),
-                    expr: Box::new(parse_unary_expression_rule(expr_pair, script)?),
-                },
-                location,
-                span,
-            ))
+    let (inner_pairs, location, span) = rules::get_data_from_rule(&pair, script);
+    let mut ops = Vec::new();
+    for next_rule in inner_pairs {
+        match next_rule.as_rule() {
+            Rule::unary_op => ops.push(next_rule.as_str().to_string()),
+            Rule::postfix_expression => {
+                let mut node = parse_postfix_expression_rule(next_rule, script)?;
+                // Prefix operators apply innermost-first, so `- -x` becomes `-(-x)`.
+                for op in ops.into_iter().rev() {
+                    node = AstNode::new(
+                        AstNodeKind::UnaryOp {
+                            op,
+                            expr: Box::new(node),
+                        },
+                        location.clone(),
+                        span.clone(),
+                    );
+                }
+                return Ok(node);
+            }
+            _ => {
+                return Err(Box::<dyn MainstageErrorExt>::from(Box::new(
+                    crate::ast::err::SyntaxError::with(
+                        crate::Level::Error,
+                        "Unexpected unary expression type.".into(),
+                        "mainstage.expr.parse_unary_expression_rule".into(),
+                        location,
+                        span,
+                    ),
+                )));
+            }
         }
-        Rule::postfix_expression => parse_postfix_expression_rule(next_rule, script),
-        _ => Err(Box::<dyn MainstageErrorExt>::from(Box::new(
-            crate::ast::err::SyntaxError::with(
-                crate::Level::Error,
-                "Unexpected unary expression type.".into(),
-                "mainstage.expr.parse_unary_expression_rule".into(),
-                location,
-                span,
-            ),
-        ))),
     }
+    Err(Box::<dyn MainstageErrorExt>::from(Box::new(
+        crate::ast::err::SyntaxError::with(
+            crate::Level::Error,
+            "Missing operand for unary operator.".into(),
+            "mainstage.expr.parse_unary_expression_rule".into(),
+            location,
+            span,
+        ),
+    )))
 }
 
 fn parse_postfix_expression_rule(
     pair: pest::iterators::Pair<Rule>,
     script: &script::Script,
 ) -> Result<AstNode, Box<dyn MainstageErrorExt>> {
-    let (mut inner_pair, location, span) = rules::get_data_from_rule(&pair, script);
-    let next_rule = rules::fetch_next_pair(&mut inner_pair, &location, &span)?;
-    match next_rule.as_rule() {
-        Rule::primary_expression => parse_primary_expression_rule(next_rule, script),
-        _ => Err(Box::<dyn MainstageErrorExt>::from(Box::new(
+    let (mut inner_pairs, location, span) = rules::get_data_from_rule(&pair, script);
+    let next_rule = rules::fetch_next_pair(&mut inner_pairs, &location, &span)?;
+    let mut node = match next_rule.as_rule() {
+        Rule::primary_expression => parse_primary_expression_rule(next_rule, script)?,
+        _ => {
+            return Err(Box::<dyn MainstageErrorExt>::from(Box::new(
+                crate::ast::err::SyntaxError::with(
+                    crate::Level::Error,
+                    "Unexpected postfix expression type.".into(),
+                    "mainstage.expr.parse_postfix_expression_rule".into(),
+                    location,
+                    span,
+                ),
+            )));
+        }
+    };
+
+    // Each postfix op wraps everything to its left, so `obj.fn(a)[i]` nests as Index(Call(Member)).
+    for op_pair in inner_pairs {
+        node = parse_postfix_op_rule(node, op_pair, script, &location, &span)?;
+    }
+
+    Ok(node)
+}
+
+fn parse_postfix_op_rule(
+    operand: AstNode,
+    pair: pest::iterators::Pair<Rule>,
+    script: &script::Script,
+    location: &Option<crate::location::Location>,
+    span: &Option<crate::location::Span>,
+) -> Result<AstNode, Box<dyn MainstageErrorExt>> {
+    // The resulting node spans from the start of the operand to the end of this op.
+    let op_span = rules::get_span_from_pair(&pair, script);
+    let node_span = match (span, op_span) {
+        (Some(outer), Some(op)) => Some(crate::location::Span::new(outer.start.clone(), op.end)),
+        (outer, _) => outer.clone(),
+    };
+
+    let text = pair.as_str();
+    let mut inner_pairs = pair.clone().into_inner();
+    let kind = if text.starts_with('(') {
+        let args = match inner_pairs.next() {
+            Some(arguments_pair) => parse_call_arguments_rule(arguments_pair, script)?,
+            None => Vec::new(),
+        };
+        AstNodeKind::Call {
+            callee: Box::new(operand),
+            args,
+        }
+    } else if text.starts_with('.') {
+        let member_pair = rules::fetch_next_pair(&mut inner_pairs, location, span)?;
+        AstNodeKind::Member {
+            object: Box::new(operand),
+            member: member_pair.as_str().to_string(),
+        }
+    } else if text.starts_with('[') {
+        let index_pair = rules::fetch_next_pair(&mut inner_pairs, location, span)?;
+        AstNodeKind::Index {
+            object: Box::new(operand),
+            index: Box::new(parse_expression_rule(index_pair, script)?),
+        }
+    } else if text == "++" || text == "--" {
+        AstNodeKind::PostfixOp {
+            op: text.to_string(),
+            expr: Box::new(operand),
+        }
+    } else {
+        return Err(Box::<dyn MainstageErrorExt>::from(Box::new(
             crate::ast::err::SyntaxError::with(
                 crate::Level::Error,
-                "Unexpected postfix expression type.".into(),
-                "mainstage.expr.parse_postfix_expression_rule".into(),
-                location,
-                span,
+                format!("Unexpected postfix operator '{}'.", text),
+                "mainstage.expr.parse_postfix_op_rule".into(),
+                location.clone(),
+                span.clone(),
             ),
-        ))),
-    }
+        )));
+    };
+
+    Ok(AstNode::new(kind, location.clone(), node_span))
+}
+
+fn parse_call_arguments_rule(
+    pair: pest::iterators::Pair<Rule>,
+    script: &script::Script,
+) -> Result<Vec<AstNode>, Box<dyn MainstageErrorExt>> {
+    let (inner_pairs, location, span) = rules::get_data_from_rule(&pair, script);
+    inner_pairs
+        .map(|param_pair| {
+            let mut param_inner = param_pair.into_inner();
+            let expr_pair = rules::fetch_next_pair(&mut param_inner, &location, &span)?;
+            parse_expression_rule(expr_pair, script)
+        })
+        .collect()
 }
 
 fn parse_primary_expression_rule(
@@ -1,19 +1,27 @@
+use serde::Serialize;
+
 use super::node::AstNode;
 
-#[derive(Debug, Clone, PartialEq)]
+/// New node kinds are added as the language grows, so matches outside this crate need a
+/// wildcard arm. Serialized with the variant name in a `kind` field.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind")]
+#[non_exhaustive]
 pub enum AstNodeKind {
     Script { body: Vec<AstNode> },
-    Import { module: String },
+    Import { module: String, alias: String },
     Include { file: String },
 
     Statement,
     Arguments { args: Vec<AstNode> },
 
-    Workspace { name: String, body: Box<AstNode> },
-    Project { name: String, body: Box<AstNode> },
-    Stage { name: String, args: Option<Box<AstNode>>, body: Box<AstNode> },
+    Workspace { name: String, attributes: Vec<String>, body: Box<AstNode> },
+    Project { name: String, attributes: Vec<String>, body: Box<AstNode> },
+    Stage { name: String, attributes: Vec<String>, args: Option<Box<AstNode>>, body: Box<AstNode> },
 
     Block { statements: Vec<AstNode> },
+    /// Workspace lifecycle handler; `event` is `on_failure` or `on_success`.
+    Handler { event: String, body: Box<AstNode> },
 
     If { condition: Box<AstNode>, body: Box<AstNode> },
     IfElse { condition: Box<AstNode>, if_body: Box<AstNode>, else_body: Box<AstNode> },
@@ -22,12 +30,20 @@ pub enum AstNodeKind {
     ForTo { initializer: Box<AstNode>, limit: Box<AstNode>, body: Box<AstNode> },
     While { condition: Box<AstNode>, body: Box<AstNode> },
 
+    Retry { attempts: Box<AstNode>, backoff: Option<Box<AstNode>>, body: Box<AstNode> },
+    Timeout { duration: Box<AstNode>, body: Box<AstNode> },
+    /// `binding` names the caught error and is only in scope inside `handler`.
+    Try { body: Box<AstNode>, binding: String, handler: Box<AstNode> },
+
     UnaryOp { op: String, expr: Box<AstNode> },
+    PostfixOp { op: String, expr: Box<AstNode> },
     BinaryOp { left: Box<AstNode>, op: String, right: Box<AstNode> },
     Assignment { target: Box<AstNode>, value: Box<AstNode> },
 
     Command { name: String, arg: String },
     Call { callee: Box<AstNode>, args: Vec<AstNode> },
+    Member { object: Box<AstNode>, member: String },
+    Index { object: Box<AstNode>, index: Box<AstNode> },
     Return { value: Option<Box<AstNode>> },
 
     Identifier { name: String },
@@ -1,17 +1,15 @@
-pub mod err;
-pub mod kind;
-pub mod node;
-pub mod rules;
-pub mod stmt;
-pub mod expr;
+pub(crate) mod err;
+pub(crate) mod kind;
+pub(crate) mod node;
+pub(crate) mod rules;
+pub(crate) mod stmt;
+pub(crate) mod expr;
 
-/// Re-exporting for easier access
+/// Re-exporting for easier access; the submodules themselves are private.
 pub use err::*;
 pub use kind::AstNodeKind;
 pub use node::AstNode;
-pub use rules::RulesParser;
-
-use crate::ast::rules::Rule;
+pub use rules::{Rule, RulesParser};
 use crate::{Level, MainstageErrorExt, Script};
 
 use pest::Parser;
@@ -20,14 +18,8 @@ use stmt::*; // Import the Parser trait // Import the Rule enum generated by pes
 pub fn generate_rules_from_script(
     script: &Script
 ) -> Result<pest::iterators::Pairs<'_, Rule>, Box<dyn MainstageErrorExt>> {
-    RulesParser::parse(Rule::script, &script.content).map_err(|_| {
-        Box::<dyn MainstageErrorExt>::from(Box::new(err::SyntaxError::with(
-            Level::Error,
-            "There was a syntax error in the script.".into(),
-            "mainstage.ast.generate_rules_from_script".into(),
-            None,
-            None,
-        )))
+    RulesParser::parse(Rule::script, &script.content).map_err(|e| {
+        rules::syntax_error_from_pest(e, script, "mainstage.ast.generate_rules_from_script")
     })
 }
 
@@ -42,14 +34,8 @@ pub fn generate_ast_from_source(script: &Script) -> Result<AstNode, Box<dyn Main
             None,
         )))
     } else {
-        let rules = RulesParser::parse(Rule::script, &script.content).map_err(|_| {
-            Box::<dyn MainstageErrorExt>::from(Box::new(err::SyntaxError::with(
-                Level::Error,
-                "There was a syntax error in the script.".into(),
-                "mainstage.ast.generate_ast_from_source".into(),
-                None,
-                None,
-            )))
+        let rules = RulesParser::parse(Rule::script, &script.content).map_err(|e| {
+            rules::syntax_error_from_pest(e, script, "mainstage.ast.generate_ast_from_source")
         })?;
 
         let first_rule = rules.into_iter().next().unwrap();
@@ -60,6 +46,7 @@ pub fn generate_ast_from_source(script: &Script) -> Result<AstNode, Box<dyn Main
         if first_rule.as_rule() == Rule::script {
             let body = first_rule
                 .into_inner()
+                .filter(|f| f.as_rule() != Rule::EOI)
                 .map(|f| parse_item_rule(f, script))
                 .collect::<Result<Vec<AstNode>, Box<dyn MainstageErrorExt>>>()?;
             Ok(AstNode::new(AstNodeKind::Script { body }, location, span))
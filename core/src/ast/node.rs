@@ -1,13 +1,20 @@
+use serde::Serialize;
+
 use crate::location;
 
 use super::kind::AstNodeKind;
 
-#[derive(Clone, PartialEq)]
+/// Serializes as the node's `kind` fields alongside `id`, `location` and `span`.
+#[derive(Clone, PartialEq, Serialize)]
 pub struct AstNode {
     id: usize,
+    #[serde(flatten)]
     pub node_type: AstNodeKind,
     pub location: Option<location::Location>,
     pub span: Option<location::Span>,
+    /// Lints suppressed on this node and its descendants by `@allow(...)`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub allow: Vec<String>,
 }
 
 impl AstNode {
@@ -28,6 +35,7 @@ impl AstNode {
             node_type,
             location,
             span,
+            allow: Vec::new(),
         }
     }
 
@@ -40,6 +48,11 @@ impl AstNode {
         self
     }
 
+    pub fn with_allow(mut self, lints: Vec<String>) -> Self {
+        self.allow = lints;
+        self
+    }
+
     pub fn get_id(&self) -> usize {
         self.id
     }
@@ -52,6 +65,68 @@ impl AstNode {
     pub fn get_span(&self) -> Option<&crate::location::Span> {
         self.span.as_ref()
     }
+
+    /// Returns the direct child nodes in source order.
+    pub fn children(&self) -> Vec<&AstNode> {
+        match &self.node_type {
+            AstNodeKind::Script { body } => body.iter().collect(),
+            AstNodeKind::Arguments { args } => args.iter().collect(),
+            AstNodeKind::Workspace { body, .. }
+            | AstNodeKind::Project { body, .. }
+            | AstNodeKind::Handler { body, .. } => vec![body.as_ref()],
+            AstNodeKind::Stage { args, body, .. } => args
+                .iter()
+                .map(|a| a.as_ref())
+                .chain(std::iter::once(body.as_ref()))
+                .collect(),
+            AstNodeKind::Block { statements } => statements.iter().collect(),
+            AstNodeKind::If { condition, body } => vec![condition.as_ref(), body.as_ref()],
+            AstNodeKind::IfElse {
+                condition,
+                if_body,
+                else_body,
+            } => vec![condition.as_ref(), if_body.as_ref(), else_body.as_ref()],
+            AstNodeKind::ForIn { iterable, body, .. } => vec![iterable.as_ref(), body.as_ref()],
+            AstNodeKind::ForTo {
+                initializer,
+                limit,
+                body,
+            } => vec![initializer.as_ref(), limit.as_ref(), body.as_ref()],
+            AstNodeKind::While { condition, body } => vec![condition.as_ref(), body.as_ref()],
+            AstNodeKind::Timeout { duration, body } => vec![duration.as_ref(), body.as_ref()],
+            AstNodeKind::Try { body, handler, .. } => vec![body.as_ref(), handler.as_ref()],
+            AstNodeKind::Retry {
+                attempts,
+                backoff,
+                body,
+            } => std::iter::once(attempts.as_ref())
+                .chain(backoff.iter().map(|b| b.as_ref()))
+                .chain(std::iter::once(body.as_ref()))
+                .collect(),
+            AstNodeKind::UnaryOp { expr, .. } | AstNodeKind::PostfixOp { expr, .. } => {
+                vec![expr.as_ref()]
+            }
+            AstNodeKind::BinaryOp { left, right, .. } => vec![left.as_ref(), right.as_ref()],
+            AstNodeKind::Assignment { target, value } => vec![target.as_ref(), value.as_ref()],
+            AstNodeKind::Call { callee, args } => std::iter::once(callee.as_ref())
+                .chain(args.iter())
+                .collect(),
+            AstNodeKind::Member { object, .. } => vec![object.as_ref()],
+            AstNodeKind::Index { object, index } => vec![object.as_ref(), index.as_ref()],
+            AstNodeKind::Return { value } => value.iter().map(|v| v.as_ref()).collect(),
+            AstNodeKind::List { elements } => elements.iter().collect(),
+            AstNodeKind::Import { .. }
+            | AstNodeKind::Include { .. }
+            | AstNodeKind::Statement
+            | AstNodeKind::Command { .. }
+            | AstNodeKind::Identifier { .. }
+            | AstNodeKind::String { .. }
+            | AstNodeKind::Integer { .. }
+            | AstNodeKind::Float { .. }
+            | AstNodeKind::Bool { .. }
+            | AstNodeKind::Null => Vec::new(),
+        }
+    }
 }
 
 use std::fmt;
@@ -4,7 +4,6 @@ use crate::location;
 
 #[derive(Parser)]
 #[grammar = "grammar.pest"]
-
 pub struct RulesParser;
 
 pub(crate) fn fetch_next_pair<'a>(
@@ -35,8 +34,8 @@ pub(crate) fn get_data_from_rule<'a>(
     Option<crate::location::Span>,
 ) {
     let inner_rules = rule.clone().into_inner();
-    let span = get_span_from_pair(&rule, script);
-    let location = get_location_from_pair(&rule, script);
+    let span = get_span_from_pair(rule, script);
+    let location = get_location_from_pair(rule, script);
     (inner_rules, location, span)
 }
 
@@ -46,7 +45,7 @@ pub fn get_location_from_pair(
 ) -> Option<crate::location::Location> {
     let span = rule.as_span();
     Some(crate::location::Location {
-        file: script.name.clone(),
+        file: script.path.display().to_string(),
         line: span.start_pos().line_col().0,
         column: span.start_pos().line_col().1,
     })
@@ -59,14 +58,38 @@ pub fn get_span_from_pair(
     let span = rule.as_span();
     Some(crate::location::Span {
         start: crate::location::Location {
-            file: script.name.clone(),
+            file: script.path.display().to_string(),
             line: span.start_pos().line_col().0,
             column: span.start_pos().line_col().1,
         },
         end: crate::location::Location {
-            file: script.name.clone(),
+            file: script.path.display().to_string(),
             line: span.end_pos().line_col().0,
             column: span.end_pos().line_col().1,
         },
     })
 }
+
+/// Converts a pest parse failure into a `SyntaxError` pointing at the offending position.
+pub(crate) fn syntax_error_from_pest(
+    error: pest::error::Error<Rule>,
+    script: &crate::script::Script,
+    issuer: &str,
+) -> Box<dyn crate::ast::MainstageErrorExt> {
+    let ((start_line, start_col), (end_line, end_col)) = match error.line_col {
+        pest::error::LineColLocation::Pos(pos) => (pos, pos),
+        pest::error::LineColLocation::Span(start, end) => (start, end),
+    };
+    let start = crate::location::Location::new(script.path.display().to_string(), start_line, start_col);
+    let end = crate::location::Location::new(script.path.display().to_string(), end_line, end_col);
+    Box::new(crate::ast::err::SyntaxError::with(
+        crate::Level::Error,
+        format!(
+            "There was a syntax error in the script: {}.",
+            error.variant.message()
+        ),
+        issuer.into(),
+        Some(start.clone()),
+        Some(crate::location::Span::new(start, end)),
+    ))
+}
@@ -36,22 +36,37 @@ fn parse_statement_rule(
     script: &script::Script,
 ) -> Result<AstNode, Box<dyn MainstageErrorExt>> {
     let (mut inner_pairs, location, span) = rules::get_data_from_rule(&pair, script);
+    let allow = parse_allow_attributes(&mut inner_pairs);
     let next_rule = rules::fetch_next_pair(&mut inner_pairs, &location, &span)?;
-    match next_rule.as_rule() {
+    let statement = match next_rule.as_rule() {
         Rule::terminated_statement => parse_terminated_statement_rule(next_rule, script),
         Rule::loop_stmt => parse_loop_statement_rule(next_rule, script),
         Rule::conditional_stmt => parse_conditional_statement_rule(next_rule, script),
+        Rule::guard_stmt => parse_guard_statement_rule(next_rule, script),
         Rule::block => parse_block_rule(next_rule, script),
         _ => Err(Box::<dyn MainstageErrorExt>::from(Box::new(
             crate::ast::err::SyntaxError::with(
                 crate::Level::Error,
-                format!("Unexpected statement type: {:?}", next_rule.as_rule()).into(),
+                format!("Unexpected statement type: {:?}", next_rule.as_rule()),
                 "mainstage.stmt.parse_statement_rule".into(),
                 location,
                 span,
             ),
         ))),
+    };
+    statement.map(|node| node.with_allow(allow))
+}
+
+/// Collects the lint names from any leading `@allow(...)` attributes.
+fn parse_allow_attributes(pairs: &mut pest::iterators::Pairs<Rule>) -> Vec<String> {
+    let mut lints = Vec::new();
+    while let Some(pair) = pairs.peek()
+        && pair.as_rule() == Rule::allow_attr
+    {
+        pairs.next();
+        lints.extend(pair.into_inner().map(|p| p.as_str().to_string()));
     }
+    lints
 }
 
 fn parse_terminated_statement_rule(
@@ -61,30 +76,41 @@ fn parse_terminated_statement_rule(
     let (mut inner_pairs, location, span) = rules::get_data_from_rule(&pair, script);
     let next_rule = rules::fetch_next_pair(&mut inner_pairs, &location, &span)?;
     match next_rule.as_rule() {
-        Rule::include_stmt => Ok(AstNode::new(
-            AstNodeKind::Include {
-                file: next_rule.as_str().to_string(),
-            },
-            location,
-            span,
-        )),
-        Rule::import_stmt => Ok(AstNode::new(
-            AstNodeKind::Import {
-                module: next_rule.as_str().to_string(),
-            },
-            location,
-            span,
-        )),
-        Rule::assignment_stmt => parse_assignment_statement_rule(next_rule, script),
-        Rule::expression_stmt => super::expr::parse_expression_rule(next_rule, script),
-        Rule::return_stmt => {
-            // Placeholder implementation
+        Rule::include_stmt => {
+            let mut include_pairs = next_rule.into_inner();
+            let file_pair = rules::fetch_next_pair(&mut include_pairs, &location, &span)?;
             Ok(AstNode::new(
-                AstNodeKind::Return { value: None },
+                AstNodeKind::Include {
+                    file: file_pair.as_str().trim_matches('"').to_string(),
+                },
                 location,
                 span,
             ))
         }
+        Rule::import_stmt => {
+            let mut import_pairs = next_rule.into_inner();
+            let module_pair = rules::fetch_next_pair(&mut import_pairs, &location, &span)?;
+            let alias_pair = rules::fetch_next_pair(&mut import_pairs, &location, &span)?;
+            Ok(AstNode::new(
+                AstNodeKind::Import {
+                    module: module_pair.as_str().trim_matches('"').to_string(),
+                    alias: alias_pair.as_str().to_string(),
+                },
+                location,
+                span,
+            ))
+        }
+        Rule::assignment_stmt => parse_assignment_statement_rule(next_rule, script),
+        Rule::expression_stmt => super::expr::parse_expression_rule(next_rule, script),
+        Rule::return_stmt => {
+            let value = match next_rule.into_inner().next() {
+                Some(expr_pair) => Some(Box::new(super::expr::parse_expression_rule(
+                    expr_pair, script,
+                )?)),
+                None => None,
+            };
+            Ok(AstNode::new(AstNodeKind::Return { value }, location, span))
+        }
         _ => Err(Box::<dyn MainstageErrorExt>::from(Box::new(
             crate::ast::err::SyntaxError::with(
                 crate::Level::Error,
@@ -154,17 +180,15 @@ fn parse_assignment_statement_rule(
             ))
         }
 
-        _ => {
-            return Err(Box::<dyn MainstageErrorExt>::from(Box::new(
-                crate::ast::err::SyntaxError::with(
-                    crate::Level::Error,
-                    "Expected assignment operator.".into(),
-                    "mainstage.stmt.parse_assignment_statement_rule".into(),
-                    location,
-                    span,
-                ),
-            )));
-        }
+        _ => Err(Box::<dyn MainstageErrorExt>::from(Box::new(
+            crate::ast::err::SyntaxError::with(
+                crate::Level::Error,
+                "Expected assignment operator.".into(),
+                "mainstage.stmt.parse_assignment_statement_rule".into(),
+                location,
+                span,
+            ),
+        ))),
     }
 }
 
@@ -173,27 +197,33 @@ fn parse_declaration_rule(
     script: &script::Script,
 ) -> Result<AstNode, Box<dyn MainstageErrorExt>> {
     let (mut inner_pairs, location, span) = rules::get_data_from_rule(&pair, script);
+    let allow = parse_allow_attributes(&mut inner_pairs);
     let next_pair = rules::fetch_next_pair(&mut inner_pairs, &location, &span)?;
-    let mut inner_pairs = next_pair.clone().into_inner();
-    match next_pair.as_rule() {
+    // Locate the declaration itself rather than any attributes in front of it.
+    let (mut inner_pairs, location, span) = rules::get_data_from_rule(&next_pair, script);
+    let declaration = match next_pair.as_rule() {
         Rule::workspace_decl => {
-            let identifier_pair = rules::fetch_next_pair(&mut inner_pairs, &location, &span)?;
+            let (attributes, identifier_pair) =
+                parse_declaration_header(&mut inner_pairs, &location, &span)?;
             let body_pair = rules::fetch_next_pair(&mut inner_pairs, &location, &span)?;
             Ok(AstNode::new(
                 AstNodeKind::Workspace {
                     name: identifier_pair.as_str().to_string(),
-                    body: Box::new(parse_block_rule(body_pair, script)?),
+                    attributes,
+                    body: Box::new(parse_workspace_block_rule(body_pair, script)?),
                 },
                 location,
                 span,
             ))
         }
         Rule::project_decl => {
-            let identifier_pair = rules::fetch_next_pair(&mut inner_pairs, &location, &span)?;
+            let (attributes, identifier_pair) =
+                parse_declaration_header(&mut inner_pairs, &location, &span)?;
             let body_pair = rules::fetch_next_pair(&mut inner_pairs, &location, &span)?;
             Ok(AstNode::new(
                 AstNodeKind::Project {
                     name: identifier_pair.as_str().to_string(),
+                    attributes,
                     body: Box::new(parse_block_rule(body_pair, script)?),
                 },
                 location,
@@ -201,11 +231,11 @@ fn parse_declaration_rule(
             ))
         }
         Rule::stage_decl => {
-            // Attribute parsing can be added here in the future. Will likely be deprecated.
-            let identifier_pair = rules::fetch_next_pair(&mut inner_pairs, &location, &span)?;
+            let (attributes, identifier_pair) =
+                parse_declaration_header(&mut inner_pairs, &location, &span)?;
             let mut args_pair = None;
             let mut body_pair = None;
-            while let Some(pair) = inner_pairs.next() {
+            for pair in inner_pairs {
                 match pair.as_rule() {
                     Rule::arguments => {
                         args_pair = Some(pair);
@@ -227,6 +257,7 @@ fn parse_declaration_rule(
             Ok(AstNode::new(
                 AstNodeKind::Stage {
                     name: identifier_pair.as_str().to_string(),
+                    attributes,
                     args,
                     body: body.expect("Stage declaration must have a body"),
                 },
@@ -243,10 +274,29 @@ fn parse_declaration_rule(
                 span,
             ),
         ))),
+    };
+    declaration.map(|node| node.with_allow(allow))
+}
+
+/// Splits the optional `[attr, ...]` list off a declaration and returns it with the name pair.
+fn parse_declaration_header<'a>(
+    inner_pairs: &mut pest::iterators::Pairs<'a, Rule>,
+    location: &Option<crate::location::Location>,
+    span: &Option<crate::location::Span>,
+) -> Result<(Vec<String>, pest::iterators::Pair<'a, Rule>), Box<dyn MainstageErrorExt>> {
+    let next_pair = rules::fetch_next_pair(inner_pairs, location, span)?;
+    if next_pair.as_rule() != Rule::attributes {
+        return Ok((Vec::new(), next_pair));
     }
+    let attributes = next_pair
+        .into_inner()
+        .map(|attr| attr.as_str().to_string())
+        .collect();
+    let identifier_pair = rules::fetch_next_pair(inner_pairs, location, span)?;
+    Ok((attributes, identifier_pair))
 }
 
-fn parse_arguments_rule(
+pub(crate) fn parse_arguments_rule(
     pair: pest::iterators::Pair<Rule>,
     script: &script::Script,
 ) -> Result<AstNode, Box<dyn MainstageErrorExt>> {
@@ -271,7 +321,7 @@ fn parse_block_rule(
     pair: pest::iterators::Pair<Rule>,
     script: &script::Script,
 ) -> Result<AstNode, Box<dyn MainstageErrorExt>> {
-    let (inner_pairs, _location, _span) = rules::get_data_from_rule(&pair, script);
+    let (inner_pairs, location, span) = rules::get_data_from_rule(&pair, script);
     let mut body = Vec::new();
 
     for stmt_pair in inner_pairs {
@@ -281,8 +331,61 @@ fn parse_block_rule(
 
     Ok(AstNode::new(
         AstNodeKind::Block { statements: body },
-        None,
-        None,
+        location,
+        span,
+    ))
+}
+
+/// Parses a workspace body, which is a block that may also contain lifecycle handlers.
+/// Handlers stay in source order inside the resulting `Block`; each event may appear once.
+fn parse_workspace_block_rule(
+    pair: pest::iterators::Pair<Rule>,
+    script: &script::Script,
+) -> Result<AstNode, Box<dyn MainstageErrorExt>> {
+    let (inner_pairs, location, span) = rules::get_data_from_rule(&pair, script);
+    let mut body = Vec::new();
+    let mut seen_events: Vec<String> = Vec::new();
+
+    for item_pair in inner_pairs {
+        if item_pair.as_rule() != Rule::handler_decl {
+            body.push(parse_statement_rule(item_pair, script)?);
+            continue;
+        }
+
+        let (mut handler_pairs, handler_location, handler_span) =
+            rules::get_data_from_rule(&item_pair, script);
+        let event_pair =
+            rules::fetch_next_pair(&mut handler_pairs, &handler_location, &handler_span)?;
+        let body_pair =
+            rules::fetch_next_pair(&mut handler_pairs, &handler_location, &handler_span)?;
+        let event = event_pair.as_str().to_string();
+        if seen_events.contains(&event) {
+            return Err(Box::<dyn MainstageErrorExt>::from(Box::new(
+                crate::ast::err::SyntaxError::with(
+                    crate::Level::Error,
+                    format!("Duplicate {} handler in workspace.", event),
+                    "mainstage.stmt.parse_workspace_block_rule".into(),
+                    handler_location,
+                    handler_span,
+                ),
+            )));
+        }
+        seen_events.push(event.clone());
+
+        body.push(AstNode::new(
+            AstNodeKind::Handler {
+                event,
+                body: Box::new(parse_block_rule(body_pair, script)?),
+            },
+            handler_location,
+            handler_span,
+        ));
+    }
+
+    Ok(AstNode::new(
+        AstNodeKind::Block { statements: body },
+        location,
+        span,
     ))
 }
 
@@ -335,13 +438,13 @@ fn parse_for_to_statement_rule(
     pair: pest::iterators::Pair<Rule>,
     script: &script::Script,
 ) -> Result<AstNode, Box<dyn MainstageErrorExt>> {
-    // Placeholder implementation
     let (mut inner_pairs, location, span) = rules::get_data_from_rule(&pair, script);
     let initializer_pair = rules::fetch_next_pair(&mut inner_pairs, &location, &span)?;
     let limit_pair = rules::fetch_next_pair(&mut inner_pairs, &location, &span)?;
     let body_pair = rules::fetch_next_pair(&mut inner_pairs, &location, &span)?;
 
-    let initializer_node = super::expr::parse_expression_rule(initializer_pair, script)?;
+    // The initializer is an `assignment_expr`, which has the same shape as an assignment statement.
+    let initializer_node = parse_assignment_statement_rule(initializer_pair, script)?;
     let limit_node = super::expr::parse_expression_rule(limit_pair, script)?;
     let body_node = parse_block_rule(body_pair, script)?;
 
@@ -385,12 +488,32 @@ fn parse_conditional_statement_rule(
     let next_pair = rules::fetch_next_pair(&mut inner_pairs, &location, &span)?;
     match next_pair.as_rule() {
         Rule::if_stmt => {
-            // Placeholder implementation
-            Ok(AstNode::new(AstNodeKind::Statement, location, span))
+            let mut if_pairs = next_pair.into_inner();
+            let condition_pair = rules::fetch_next_pair(&mut if_pairs, &location, &span)?;
+            let body_pair = rules::fetch_next_pair(&mut if_pairs, &location, &span)?;
+            Ok(AstNode::new(
+                AstNodeKind::If {
+                    condition: Box::new(super::expr::parse_expression_rule(condition_pair, script)?),
+                    body: Box::new(parse_block_rule(body_pair, script)?),
+                },
+                location,
+                span,
+            ))
         }
         Rule::if_else_stmt => {
-            // Placeholder implementation
-            Ok(AstNode::new(AstNodeKind::Statement, location, span))
+            let mut if_pairs = next_pair.into_inner();
+            let condition_pair = rules::fetch_next_pair(&mut if_pairs, &location, &span)?;
+            let if_body_pair = rules::fetch_next_pair(&mut if_pairs, &location, &span)?;
+            let else_body_pair = rules::fetch_next_pair(&mut if_pairs, &location, &span)?;
+            Ok(AstNode::new(
+                AstNodeKind::IfElse {
+                    condition: Box::new(super::expr::parse_expression_rule(condition_pair, script)?),
+                    if_body: Box::new(parse_block_rule(if_body_pair, script)?),
+                    else_body: Box::new(parse_block_rule(else_body_pair, script)?),
+                },
+                location,
+                span,
+            ))
         }
         _ => Err(Box::<dyn MainstageErrorExt>::from(Box::new(
             crate::ast::err::SyntaxError::with(
@@ -403,3 +526,223 @@ fn parse_conditional_statement_rule(
         ))),
     }
 }
+
+fn parse_guard_statement_rule(
+    pair: pest::iterators::Pair<Rule>,
+    script: &script::Script,
+) -> Result<AstNode, Box<dyn MainstageErrorExt>> {
+    let (mut inner_pairs, location, span) = rules::get_data_from_rule(&pair, script);
+    let next_pair = rules::fetch_next_pair(&mut inner_pairs, &location, &span)?;
+    match next_pair.as_rule() {
+        Rule::retry_stmt => parse_retry_statement_rule(next_pair, script),
+        Rule::timeout_stmt => parse_timeout_statement_rule(next_pair, script),
+        Rule::try_stmt => parse_try_statement_rule(next_pair, script),
+        _ => Err(Box::<dyn MainstageErrorExt>::from(Box::new(
+            crate::ast::err::SyntaxError::with(
+                crate::Level::Error,
+                "Unexpected guarded statement type.".into(),
+                "mainstage.stmt.parse_guard_statement_rule".into(),
+                location,
+                span,
+            ),
+        ))),
+    }
+}
+
+fn parse_retry_statement_rule(
+    pair: pest::iterators::Pair<Rule>,
+    script: &script::Script,
+) -> Result<AstNode, Box<dyn MainstageErrorExt>> {
+    let (mut inner_pairs, location, span) = rules::get_data_from_rule(&pair, script);
+    let attempts_pair = rules::fetch_next_pair(&mut inner_pairs, &location, &span)?;
+    let attempts = super::expr::parse_expression_rule(attempts_pair, script)?;
+    if let Some(value) = constant_integer(&attempts)
+        && value < 1
+    {
+        return Err(Box::<dyn MainstageErrorExt>::from(Box::new(
+            crate::ast::err::SyntaxError::with(
+                crate::Level::Error,
+                format!("Retry attempts must be at least 1, found {}.", value),
+                "mainstage.stmt.parse_retry_statement_rule".into(),
+                attempts.location.clone(),
+                attempts.span.clone(),
+            ),
+        )));
+    }
+
+    let mut backoff = None;
+    let mut body = None;
+    for item_pair in inner_pairs {
+        match item_pair.as_rule() {
+            Rule::named_option => {
+                let (name, value) = parse_named_option_rule(item_pair, script)?;
+                match name.as_str() {
+                    "backoff" => {
+                        if backoff.is_some() {
+                            return Err(Box::<dyn MainstageErrorExt>::from(Box::new(
+                                crate::ast::err::SyntaxError::with(
+                                    crate::Level::Error,
+                                    "Retry option 'backoff' is given more than once.".into(),
+                                    "mainstage.stmt.parse_retry_statement_rule".into(),
+                                    value.location.clone(),
+                                    value.span.clone(),
+                                ),
+                            )));
+                        }
+                        validate_duration_literal(&value)?;
+                        backoff = Some(Box::new(value));
+                    }
+                    _ => {
+                        return Err(Box::<dyn MainstageErrorExt>::from(Box::new(
+                            crate::ast::err::SyntaxError::with(
+                                crate::Level::Error,
+                                format!("Unknown retry option '{}'; expected 'backoff'.", name),
+                                "mainstage.stmt.parse_retry_statement_rule".into(),
+                                value.location.clone(),
+                                value.span.clone(),
+                            ),
+                        )));
+                    }
+                }
+            }
+            Rule::block => body = Some(parse_block_rule(item_pair, script)?),
+            _ => {}
+        }
+    }
+
+    let body = match body {
+        Some(body) => body,
+        None => {
+            return Err(Box::<dyn MainstageErrorExt>::from(Box::new(
+                crate::ast::err::SyntaxError::with(
+                    crate::Level::Error,
+                    "Retry statement is missing its block.".into(),
+                    "mainstage.stmt.parse_retry_statement_rule".into(),
+                    location,
+                    span,
+                ),
+            )));
+        }
+    };
+
+    Ok(AstNode::new(
+        AstNodeKind::Retry {
+            attempts: Box::new(attempts),
+            backoff,
+            body: Box::new(body),
+        },
+        location,
+        span,
+    ))
+}
+
+/// Evaluates an expression made only of integer literals, `+`, `-`, `*` and `/`, so that
+/// counts like `-1` or `2 - 3` can be checked while parsing. Returns `None` for anything
+/// else, including overflow and division by zero.
+fn constant_integer(node: &AstNode) -> Option<i64> {
+    match node.get_kind() {
+        AstNodeKind::Integer { value } => Some(*value),
+        AstNodeKind::UnaryOp { op, expr } => {
+            let value = constant_integer(expr)?;
+            match op.as_str() {
+                "+" => Some(value),
+                "-" => value.checked_neg(),
+                _ => None,
+            }
+        }
+        AstNodeKind::BinaryOp { left, op, right } => {
+            let (left, right) = (constant_integer(left)?, constant_integer(right)?);
+            match op.as_str() {
+                "+" => left.checked_add(right),
+                "-" => left.checked_sub(right),
+                "*" => left.checked_mul(right),
+                "/" => left.checked_div(right),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn parse_timeout_statement_rule(
+    pair: pest::iterators::Pair<Rule>,
+    script: &script::Script,
+) -> Result<AstNode, Box<dyn MainstageErrorExt>> {
+    let (mut inner_pairs, location, span) = rules::get_data_from_rule(&pair, script);
+    let duration_pair = rules::fetch_next_pair(&mut inner_pairs, &location, &span)?;
+    let body_pair = rules::fetch_next_pair(&mut inner_pairs, &location, &span)?;
+
+    let duration = super::expr::parse_expression_rule(duration_pair, script)?;
+    validate_duration_literal(&duration)?;
+
+    Ok(AstNode::new(
+        AstNodeKind::Timeout {
+            duration: Box::new(duration),
+            body: Box::new(parse_block_rule(body_pair, script)?),
+        },
+        location,
+        span,
+    ))
+}
+
+fn parse_try_statement_rule(
+    pair: pest::iterators::Pair<Rule>,
+    script: &script::Script,
+) -> Result<AstNode, Box<dyn MainstageErrorExt>> {
+    let (mut inner_pairs, location, span) = rules::get_data_from_rule(&pair, script);
+    let body_pair = rules::fetch_next_pair(&mut inner_pairs, &location, &span)?;
+    let binding_pair = rules::fetch_next_pair(&mut inner_pairs, &location, &span)?;
+    let handler_pair = rules::fetch_next_pair(&mut inner_pairs, &location, &span)?;
+
+    Ok(AstNode::new(
+        AstNodeKind::Try {
+            body: Box::new(parse_block_rule(body_pair, script)?),
+            binding: binding_pair.as_str().to_string(),
+            handler: Box::new(parse_block_rule(handler_pair, script)?),
+        },
+        location,
+        span,
+    ))
+}
+
+/// Parses a `name = expression` option of a guarded block header.
+fn parse_named_option_rule(
+    pair: pest::iterators::Pair<Rule>,
+    script: &script::Script,
+) -> Result<(String, AstNode), Box<dyn MainstageErrorExt>> {
+    let (mut inner_pairs, location, span) = rules::get_data_from_rule(&pair, script);
+    let name_pair = rules::fetch_next_pair(&mut inner_pairs, &location, &span)?;
+    let value_pair = rules::fetch_next_pair(&mut inner_pairs, &location, &span)?;
+    Ok((
+        name_pair.as_str().to_string(),
+        super::expr::parse_expression_rule(value_pair, script)?,
+    ))
+}
+
+/// Checks that a string literal used as a duration looks like `500ms`, `2s`, `5m` or `1h`.
+/// Non-literal expressions are left for the runtime to validate.
+fn validate_duration_literal(node: &AstNode) -> Result<(), Box<dyn MainstageErrorExt>> {
+    let AstNodeKind::String { value } = node.get_kind() else {
+        return Ok(());
+    };
+    let text = value.trim_matches('"');
+    let digits_end = text
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(digits_end);
+    if !number.is_empty() && matches!(unit, "ms" | "s" | "m" | "h") {
+        return Ok(());
+    }
+    Err(Box::<dyn MainstageErrorExt>::from(Box::new(
+        crate::ast::err::SyntaxError::with(
+            crate::Level::Error,
+            format!(
+                "Invalid duration {}; expected a whole number followed by ms, s, m or h.",
+                value
+            ),
+            "mainstage.stmt.validate_duration_literal".into(),
+            node.location.clone(),
+            node.span.clone(),
+        ),
+    )))
+}
@@ -0,0 +1,296 @@
+//! Project configuration read from `mainstage.toml`.
+//!
+//! The file is discovered by walking upward from a starting directory, so the CLI, the
+//! language server and embedders all resolve the same settings for a given script.
+//! Command-line flags are expected to be applied on top of the loaded values.
+
+pub mod user;
+
+pub use user::UserConfig;
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::MainstageErrorExt;
+use crate::analyzer::lints::{self, LintLevel};
+use crate::location::{Location, Span};
+
+/// Name of the project configuration file.
+pub const CONFIG_FILE_NAME: &str = "mainstage.toml";
+
+/// Highest accepted `opt_level`.
+pub const MAX_OPT_LEVEL: u8 = 3;
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+#[non_exhaustive]
+pub struct Config {
+    /// File the configuration was read from; `None` for the built-in defaults.
+    #[serde(skip)]
+    pub path: Option<PathBuf>,
+    /// Script used when the CLI is not given one.
+    pub script: Option<PathBuf>,
+    /// Directories searched for plugins, in order.
+    pub plugin_dirs: Vec<PathBuf>,
+    /// Optimization level, `0` to `3`.
+    pub opt_level: Option<u8>,
+    /// Maximum number of parallel jobs.
+    pub jobs: Option<usize>,
+    /// Directory build outputs and dumps are written to.
+    pub out_dir: Option<PathBuf>,
+    /// Where cached build artifacts are stored; takes precedence over the per-user setting.
+    pub cache_dir: Option<PathBuf>,
+    /// Environment variables set while a script runs.
+    pub env: BTreeMap<String, String>,
+    /// Lint severities from the `[lints]` table, keyed by lint or group name.
+    pub lints: BTreeMap<String, LintLevel>,
+    /// Named overrides selected with `--profile`.
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+/// A named set of overrides; unset fields keep the top-level value.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+#[non_exhaustive]
+pub struct Profile {
+    pub plugin_dirs: Option<Vec<PathBuf>>,
+    pub opt_level: Option<u8>,
+    pub jobs: Option<usize>,
+    pub out_dir: Option<PathBuf>,
+    pub cache_dir: Option<PathBuf>,
+    pub env: BTreeMap<String, String>,
+    pub lints: BTreeMap<String, LintLevel>,
+}
+
+impl Config {
+    /// Looks for `mainstage.toml` in `start` and each of its ancestors and loads the first
+    /// one found. Returns `Ok(None)` when there is no configuration file.
+    pub fn discover(start: &Path) -> Result<Option<Config>, Box<dyn MainstageErrorExt>> {
+        for dir in start.ancestors() {
+            let candidate = dir.join(CONFIG_FILE_NAME);
+            if candidate.is_file() {
+                return Config::load(&candidate).map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Loads a configuration file. Relative paths in it are resolved against the
+    /// directory containing the file.
+    pub fn load(path: &Path) -> Result<Config, Box<dyn MainstageErrorExt>> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            ConfigError::boxed(
+                format!("Could not read {}: {}.", path.display(), e),
+                "mainstage.config.load",
+                path,
+                None,
+            )
+        })?;
+        let mut config = Config::parse(&content, path)?;
+        let base = path.parent().unwrap_or_else(|| Path::new(""));
+        config.resolve_paths(base);
+        config.path = Some(path.to_path_buf());
+        Ok(config)
+    }
+
+    /// Parses configuration text. `path` is only used for error locations.
+    pub fn parse(content: &str, path: &Path) -> Result<Config, Box<dyn MainstageErrorExt>> {
+        let config: Config = toml::from_str(content).map_err(|e| {
+            let span = e.span().map(|range| {
+                Span::new(
+                    offset_location(content, range.start, path),
+                    offset_location(content, range.end, path),
+                )
+            });
+            ConfigError::boxed(
+                format!("Invalid {}: {}", CONFIG_FILE_NAME, e.message().trim_end()),
+                "mainstage.config.parse",
+                path,
+                span,
+            )
+        })?;
+        config.validate(path)?;
+        Ok(config)
+    }
+
+    /// Returns the configuration with the named profile's overrides applied.
+    pub fn with_profile(&self, name: &str) -> Result<Config, Box<dyn MainstageErrorExt>> {
+        let Some(profile) = self.profiles.get(name) else {
+            let path = self
+                .path
+                .clone()
+                .unwrap_or_else(|| PathBuf::from(CONFIG_FILE_NAME));
+            return Err(ConfigError::boxed(
+                format!("Unknown profile '{}'.", name),
+                "mainstage.config.with_profile",
+                &path,
+                None,
+            ));
+        };
+        let mut merged = self.clone();
+        if let Some(plugin_dirs) = &profile.plugin_dirs {
+            merged.plugin_dirs = plugin_dirs.clone();
+        }
+        merged.opt_level = profile.opt_level.or(self.opt_level);
+        merged.jobs = profile.jobs.or(self.jobs);
+        merged.out_dir = profile.out_dir.clone().or_else(|| self.out_dir.clone());
+        merged.cache_dir = profile.cache_dir.clone().or_else(|| self.cache_dir.clone());
+        merged
+            .env
+            .extend(profile.env.iter().map(|(k, v)| (k.clone(), v.clone())));
+        merged
+            .lints
+            .extend(profile.lints.iter().map(|(k, v)| (k.clone(), *v)));
+        Ok(merged)
+    }
+
+    /// Appends machine-level defaults from the per-user configuration; project settings
+    /// come first so they take precedence.
+    pub fn with_user_defaults(mut self, user: &UserConfig) -> Config {
+        self.plugin_dirs.extend(user.plugin_dirs.iter().cloned());
+        if self.cache_dir.is_none() {
+            self.cache_dir = user.cache_dir.clone();
+        }
+        self
+    }
+
+    fn validate(&self, path: &Path) -> Result<(), Box<dyn MainstageErrorExt>> {
+        let levels = std::iter::once(("opt_level".to_string(), self.opt_level)).chain(
+            self.profiles
+                .iter()
+                .map(|(name, p)| (format!("profiles.{}.opt_level", name), p.opt_level)),
+        );
+        for (key, level) in levels {
+            if let Some(level) = level
+                && level > MAX_OPT_LEVEL
+            {
+                return Err(ConfigError::boxed(
+                    format!(
+                        "{} must be between 0 and {}, found {}.",
+                        key, MAX_OPT_LEVEL, level
+                    ),
+                    "mainstage.config.validate",
+                    path,
+                    None,
+                ));
+            }
+        }
+        let jobs = std::iter::once(("jobs".to_string(), self.jobs)).chain(
+            self.profiles
+                .iter()
+                .map(|(name, p)| (format!("profiles.{}.jobs", name), p.jobs)),
+        );
+        for (key, jobs) in jobs {
+            if jobs == Some(0) {
+                return Err(ConfigError::boxed(
+                    format!("{} must be at least 1.", key),
+                    "mainstage.config.validate",
+                    path,
+                    None,
+                ));
+            }
+        }
+        let lint_names = self
+            .lints
+            .keys()
+            .map(|name| ("lints".to_string(), name))
+            .chain(self.profiles.iter().flat_map(|(profile, p)| {
+                p.lints
+                    .keys()
+                    .map(move |name| (format!("profiles.{}.lints", profile), name))
+            }));
+        for (table, name) in lint_names {
+            if !lints::is_known(name) {
+                return Err(ConfigError::boxed(
+                    format!("Unknown lint '{}' in [{}].", name, table),
+                    "mainstage.config.validate",
+                    path,
+                    None,
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn resolve_paths(&mut self, base: &Path) {
+        let resolve = |p: &mut PathBuf| *p = base.join(&*p);
+        self.script.iter_mut().for_each(resolve);
+        self.plugin_dirs.iter_mut().for_each(resolve);
+        self.out_dir.iter_mut().for_each(resolve);
+        self.cache_dir.iter_mut().for_each(resolve);
+        for profile in self.profiles.values_mut() {
+            profile.plugin_dirs.iter_mut().flatten().for_each(resolve);
+            profile.out_dir.iter_mut().for_each(resolve);
+            profile.cache_dir.iter_mut().for_each(resolve);
+        }
+    }
+}
+
+/// Converts a byte offset into a 1-based line/column location.
+fn offset_location(content: &str, offset: usize, path: &Path) -> Location {
+    let before = &content[..offset.min(content.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().map_or(0, |l| l.chars().count()) + 1;
+    Location::new(path.display().to_string(), line, column)
+}
+
+#[derive(Debug, Clone)]
+pub struct ConfigError {
+    message: String,
+    issuer: String,
+    location: Option<Location>,
+    span: Option<Span>,
+}
+
+impl ConfigError {
+    pub(crate) fn boxed(
+        message: String,
+        issuer: &str,
+        path: &Path,
+        span: Option<Span>,
+    ) -> Box<dyn MainstageErrorExt> {
+        let location = Some(
+            span.as_ref()
+                .map(|s| s.start.clone())
+                .unwrap_or_else(|| Location::new(path.display().to_string(), 1, 1)),
+        );
+        Box::new(ConfigError {
+            message,
+            issuer: issuer.to_string(),
+            location,
+            span,
+        })
+    }
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl MainstageErrorExt for ConfigError {
+    fn level(&self) -> crate::Level {
+        crate::Level::Error
+    }
+
+    fn message(&self) -> String {
+        self.message.clone()
+    }
+
+    fn issuer(&self) -> String {
+        self.issuer.clone()
+    }
+
+    fn span(&self) -> Option<Span> {
+        self.span.clone()
+    }
+
+    fn location(&self) -> Option<Location> {
+        self.location.clone()
+    }
+}
@@ -0,0 +1,203 @@
+//! Machine-level defaults read from the per-user `config.toml`.
+//!
+//! The file lives in `$XDG_CONFIG_HOME/mainstage` (falling back to `~/.config/mainstage`,
+//! or `%APPDATA%\mainstage` on Windows). Keys are addressed with dotted paths such as
+//! `compilers.c` so that `mainstage config get/set` can edit any setting.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use super::ConfigError;
+use crate::MainstageErrorExt;
+
+/// Name of the per-user configuration file.
+pub const USER_CONFIG_FILE_NAME: &str = "config.toml";
+
+/// Accepted values for `color`.
+pub const COLOR_CHOICES: [&str; 3] = ["auto", "always", "never"];
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+#[non_exhaustive]
+pub struct UserConfig {
+    /// File the configuration was read from; `None` when no file exists yet.
+    #[serde(skip)]
+    pub path: Option<PathBuf>,
+    /// Plugin directories searched after the project's own.
+    pub plugin_dirs: Vec<PathBuf>,
+    /// Preferred compiler per language, e.g. `c = "clang"`.
+    pub compilers: BTreeMap<String, String>,
+    /// Where cached build artifacts are stored.
+    pub cache_dir: Option<PathBuf>,
+    /// Colored output: `auto`, `always` or `never`.
+    pub color: Option<String>,
+    #[serde(skip)]
+    table: toml::Table,
+}
+
+impl UserConfig {
+    /// Returns the location of the per-user configuration file, if a home directory is known.
+    pub fn default_path() -> Option<PathBuf> {
+        let dir = if cfg!(windows) {
+            std::env::var_os("APPDATA").map(PathBuf::from)
+        } else {
+            std::env::var_os("XDG_CONFIG_HOME")
+                .filter(|v| !v.is_empty())
+                .map(PathBuf::from)
+                .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".config")))
+        }?;
+        Some(dir.join("mainstage").join(USER_CONFIG_FILE_NAME))
+    }
+
+    /// Loads the per-user configuration from its default location. A missing file yields
+    /// the defaults.
+    pub fn load_default() -> Result<UserConfig, Box<dyn MainstageErrorExt>> {
+        match Self::default_path() {
+            Some(path) => Self::load(&path),
+            None => Ok(UserConfig::default()),
+        }
+    }
+
+    /// Loads the configuration at `path`. A missing file yields the defaults.
+    pub fn load(path: &Path) -> Result<UserConfig, Box<dyn MainstageErrorExt>> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => {
+                return Err(ConfigError::boxed(
+                    format!("Could not read {}: {}.", path.display(), e),
+                    "mainstage.config.user.load",
+                    path,
+                    None,
+                ));
+            }
+        };
+        let table: toml::Table = content.parse().map_err(|e: toml::de::Error| {
+            ConfigError::boxed(
+                format!("Invalid {}: {}", path.display(), e.message().trim_end()),
+                "mainstage.config.user.load",
+                path,
+                None,
+            )
+        })?;
+        let mut config = Self::from_table(table, path)?;
+        config.path = Some(path.to_path_buf());
+        Ok(config)
+    }
+
+    /// Returns the value stored under a dotted key, formatted as it would be written.
+    /// Strings are returned without quotes.
+    pub fn get(&self, key: &str) -> Option<String> {
+        let mut parts = key.split('.');
+        let mut value = self.table.get(parts.next()?)?;
+        for part in parts {
+            value = value.as_table()?.get(part)?;
+        }
+        Some(match value {
+            toml::Value::String(s) => s.clone(),
+            other => other.to_string(),
+        })
+    }
+
+    /// Stores `raw` under a dotted key. `raw` is read as a TOML value when it is one
+    /// (`true`, `4`, `["a", "b"]`) and as a plain string otherwise. The result must still
+    /// be a valid configuration.
+    pub fn set(&mut self, key: &str, raw: &str) -> Result<(), Box<dyn MainstageErrorExt>> {
+        let path = self.path.clone().unwrap_or_default();
+        let value = format!("value = {}", raw)
+            .parse::<toml::Table>()
+            .ok()
+            .and_then(|mut t| t.remove("value"))
+            .unwrap_or_else(|| toml::Value::String(raw.to_string()));
+
+        let mut table = self.table.clone();
+        let parts: Vec<&str> = key.split('.').collect();
+        let (last, parents) = parts.split_last().expect("split always yields one part");
+        let mut target = &mut table;
+        for part in parents {
+            let entry = target
+                .entry(part.to_string())
+                .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+            target = match entry {
+                toml::Value::Table(t) => t,
+                _ => {
+                    return Err(ConfigError::boxed(
+                        format!("Cannot set '{}': '{}' is not a table.", key, part),
+                        "mainstage.config.user.set",
+                        &path,
+                        None,
+                    ));
+                }
+            };
+        }
+        target.insert(last.to_string(), value);
+
+        let updated = Self::from_table(table, &path)?;
+        *self = UserConfig {
+            path: self.path.take(),
+            ..updated
+        };
+        Ok(())
+    }
+
+    /// Writes the configuration back to the file it was loaded from, creating the
+    /// directory if needed.
+    pub fn save(&self) -> Result<(), Box<dyn MainstageErrorExt>> {
+        let Some(path) = &self.path else {
+            return Err(ConfigError::boxed(
+                "No location to save the user configuration to.".into(),
+                "mainstage.config.user.save",
+                Path::new(USER_CONFIG_FILE_NAME),
+                None,
+            ));
+        };
+        let write = || -> std::io::Result<()> {
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            std::fs::write(path, self.table.to_string())
+        };
+        write().map_err(|e| {
+            ConfigError::boxed(
+                format!("Could not write {}: {}.", path.display(), e),
+                "mainstage.config.user.save",
+                path,
+                None,
+            )
+        })
+    }
+
+    fn from_table(
+        table: toml::Table,
+        path: &Path,
+    ) -> Result<UserConfig, Box<dyn MainstageErrorExt>> {
+        let mut config: UserConfig =
+            toml::Value::Table(table.clone())
+                .try_into()
+                .map_err(|e: toml::de::Error| {
+                    ConfigError::boxed(
+                        format!("Invalid {}: {}", path.display(), e.message().trim_end()),
+                        "mainstage.config.user.validate",
+                        path,
+                        None,
+                    )
+                })?;
+        if let Some(color) = &config.color
+            && !COLOR_CHOICES.contains(&color.as_str())
+        {
+            return Err(ConfigError::boxed(
+                format!(
+                    "color must be one of auto, always or never, found '{}'.",
+                    color
+                ),
+                "mainstage.config.user.validate",
+                path,
+                None,
+            ));
+        }
+        config.table = table;
+        Ok(config)
+    }
+}
@@ -1,7 +1,7 @@
 use std::fmt;
 
-
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub enum Level {
     Info,
     Warning,
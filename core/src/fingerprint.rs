@@ -0,0 +1,196 @@
+//! Manifests of build artifacts for reproducibility checks.
+//!
+//! A manifest lists each file a command wrote along with its size and SHA-256 digest.
+//! Paths inside the manifest's directory are stored relative to it, so a manifest can be
+//! moved together with the artifacts it describes and verified elsewhere.
+
+use std::fmt;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Manifest {
+    /// Version of Mainstage that wrote the artifacts.
+    pub version: String,
+    pub artifacts: Vec<Artifact>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Artifact {
+    pub path: PathBuf,
+    pub size: u64,
+    pub sha256: String,
+}
+
+/// An artifact that no longer matches its manifest entry.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Mismatch {
+    Missing(PathBuf),
+    /// The artifact exists but could not be read, e.g. for lack of permission.
+    Unreadable {
+        path: PathBuf,
+        error: String,
+    },
+    /// The size differs, so the contents were not hashed.
+    Resized {
+        path: PathBuf,
+        expected: u64,
+        actual: u64,
+    },
+    Changed {
+        path: PathBuf,
+        expected: String,
+        actual: String,
+    },
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Mismatch::Missing(path) => write!(f, "{} is missing", path.display()),
+            Mismatch::Unreadable { path, error } => {
+                write!(f, "{} could not be read: {}", path.display(), error)
+            }
+            Mismatch::Resized {
+                path,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{} has changed (expected {} bytes, found {})",
+                path.display(),
+                expected,
+                actual
+            ),
+            Mismatch::Changed {
+                path,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{} has changed (expected sha256 {}, found {})",
+                path.display(),
+                expected,
+                actual
+            ),
+        }
+    }
+}
+
+impl Default for Manifest {
+    fn default() -> Self {
+        Manifest {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            artifacts: Vec::new(),
+        }
+    }
+}
+
+impl Manifest {
+    /// Hashes `path` and adds it, replacing any earlier entry for the same file.
+    pub fn record(&mut self, path: &Path) -> io::Result<()> {
+        let path = std::path::absolute(path)?;
+        let artifact = Artifact {
+            size: fs::metadata(&path)?.len(),
+            sha256: sha256_file(&path)?,
+            path,
+        };
+        match self.artifacts.iter_mut().find(|a| a.path == artifact.path) {
+            Some(existing) => *existing = artifact,
+            None => self.artifacts.push(artifact),
+        }
+        Ok(())
+    }
+
+    /// Reads a manifest, resolving relative artifact paths against its directory.
+    pub fn load(path: &Path) -> io::Result<Manifest> {
+        let content = fs::read_to_string(path)?;
+        let mut manifest: Manifest = serde_json::from_str(&content)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let base = std::path::absolute(path)?
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        for artifact in &mut manifest.artifacts {
+            artifact.path = base.join(&artifact.path);
+        }
+        Ok(manifest)
+    }
+
+    /// Writes the manifest as JSON, storing paths under its directory relative to it.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let base = std::path::absolute(path)?
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        let mut stored = self.clone();
+        for artifact in &mut stored.artifacts {
+            if let Ok(relative) = artifact.path.strip_prefix(&base) {
+                artifact.path = relative.to_path_buf();
+            }
+        }
+        let json = serde_json::to_string_pretty(&stored)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        fs::write(path, json + "\n")
+    }
+
+    /// Checks every artifact and returns those that are missing, unreadable or differ.
+    /// Sizes are compared first so that a resized artifact is not hashed.
+    pub fn verify(&self) -> Vec<Mismatch> {
+        self.artifacts.iter().filter_map(verify_artifact).collect()
+    }
+}
+
+fn verify_artifact(artifact: &Artifact) -> Option<Mismatch> {
+    let path = artifact.path.clone();
+    let unreadable = |e: io::Error| match e.kind() {
+        io::ErrorKind::NotFound => Mismatch::Missing(path.clone()),
+        _ => Mismatch::Unreadable {
+            path: path.clone(),
+            error: e.to_string(),
+        },
+    };
+    let size = match fs::metadata(&artifact.path) {
+        Ok(metadata) => metadata.len(),
+        Err(e) => return Some(unreadable(e)),
+    };
+    if size != artifact.size {
+        return Some(Mismatch::Resized {
+            path: path.clone(),
+            expected: artifact.size,
+            actual: size,
+        });
+    }
+    match sha256_file(&artifact.path) {
+        Err(e) => Some(unreadable(e)),
+        Ok(actual) if actual != artifact.sha256 => Some(Mismatch::Changed {
+            path: path.clone(),
+            expected: artifact.sha256.clone(),
+            actual,
+        }),
+        Ok(_) => None,
+    }
+}
+
+/// Returns the lowercase hex SHA-256 digest of a file's contents.
+pub fn sha256_file(path: &Path) -> io::Result<String> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 8192];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
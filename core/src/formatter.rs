@@ -0,0 +1,676 @@
+//! Canonical layout for Mainstage scripts, used by `mainstage fmt`.
+//!
+//! Comments are not part of the AST, so they are recovered from the source and attached
+//! by line: a comment on its own line stays above the statement that follows it and a
+//! trailing comment stays at the end of its line. A comment inside a statement that spans
+//! several lines, such as between the arguments of a wrapped call, has no fixed position
+//! once the statement is laid out again, so it is moved to the line below the statement.
+
+use crate::ast::{AstNode, AstNodeKind, generate_ast_from_source};
+use crate::error::Level;
+use crate::location::{Location, Span};
+use crate::{MainstageErrorExt, Script};
+
+type FormatResult<T> = Result<T, Box<dyn MainstageErrorExt>>;
+
+/// Layout settings used by [`format_script_with`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct FormatOptions {
+    /// Number of spaces per indentation level.
+    pub indent_width: usize,
+    /// Lists and call arguments longer than this are broken onto one element per line.
+    pub max_width: usize,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        FormatOptions {
+            indent_width: 4,
+            max_width: 100,
+        }
+    }
+}
+
+/// Formats a script with the default [`FormatOptions`].
+pub fn format_script(script: &Script) -> Result<String, Box<dyn MainstageErrorExt>> {
+    format_script_with(script, &FormatOptions::default())
+}
+
+/// Parses `script` and prints it back in canonical form, keeping `//` comments.
+pub fn format_script_with(
+    script: &Script,
+    options: &FormatOptions,
+) -> Result<String, Box<dyn MainstageErrorExt>> {
+    let ast = generate_ast_from_source(script)?;
+    let mut formatter = Formatter {
+        options,
+        source: &script.content,
+        comments: collect_comments(&script.content),
+        next_comment: 0,
+        out: String::new(),
+        indent: 0,
+        last_line: None,
+        force_blank: false,
+    };
+    if let AstNodeKind::Script { body } = ast.get_kind() {
+        formatter.script(body)?;
+    }
+    Ok(formatter.out)
+}
+
+#[derive(Debug, Clone)]
+struct Comment {
+    line: usize,
+    text: String,
+    /// True when code precedes the comment on the same line.
+    trailing: bool,
+}
+
+/// Comments are skipped by the grammar, so they are recovered from the raw source and
+/// re-attached to statements by line number.
+fn collect_comments(source: &str) -> Vec<Comment> {
+    let mut comments = Vec::new();
+    let mut in_string = false;
+    for (index, line) in source.lines().enumerate() {
+        let mut code_seen = false;
+        let mut chars = line.char_indices().peekable();
+        while let Some((pos, c)) = chars.next() {
+            if in_string {
+                in_string = c != '"';
+            } else if c == '"' {
+                in_string = true;
+                code_seen = true;
+            } else if c == '/' && matches!(chars.peek(), Some((_, '/'))) {
+                comments.push(Comment {
+                    line: index + 1,
+                    text: line[pos..].trim_end().to_string(),
+                    trailing: code_seen,
+                });
+                break;
+            } else if !c.is_whitespace() {
+                code_seen = true;
+            }
+        }
+    }
+    comments
+}
+
+/// Binding strength used to decide where parentheses are required.
+fn precedence(node: &AstNode) -> u8 {
+    match node.get_kind() {
+        AstNodeKind::BinaryOp { op, .. } => match op.as_str() {
+            "==" | "!=" => 1,
+            "<" | ">" | "<=" | ">=" => 2,
+            "+" | "-" => 3,
+            _ => 4,
+        },
+        AstNodeKind::UnaryOp { .. } => 5,
+        _ => 6,
+    }
+}
+
+fn start_line(node: &AstNode) -> Option<usize> {
+    node.get_span()
+        .map(|s| s.start.line)
+        .or_else(|| node.get_location().map(|l| l.line))
+}
+
+fn end_line(node: &AstNode) -> Option<usize> {
+    node.get_span().map(|s| s.end.line).or_else(|| start_line(node))
+}
+
+fn is_declaration(node: &AstNode) -> bool {
+    matches!(
+        node.get_kind(),
+        AstNodeKind::Workspace { .. } | AstNodeKind::Project { .. } | AstNodeKind::Stage { .. }
+    )
+}
+
+struct Formatter<'a> {
+    options: &'a FormatOptions,
+    source: &'a str,
+    comments: Vec<Comment>,
+    next_comment: usize,
+    out: String,
+    indent: usize,
+    /// Last source line written in the current block, used to keep intentional blank lines.
+    last_line: Option<usize>,
+    /// Requests a blank line before the next item regardless of the source layout.
+    force_blank: bool,
+}
+
+impl Formatter<'_> {
+    fn script(&mut self, body: &[AstNode]) -> FormatResult<()> {
+        let mut previous_was_declaration = None;
+        for item in body {
+            let is_decl = is_declaration(item);
+            if let Some(previous) = previous_was_declaration {
+                self.force_blank = previous || is_decl;
+            }
+            self.statement(item)?;
+            previous_was_declaration = Some(is_decl);
+        }
+        self.flush_comments_before(usize::MAX);
+        Ok(())
+    }
+
+    fn write_line(&mut self, text: &str) {
+        self.out
+            .push_str(&" ".repeat(self.indent * self.options.indent_width));
+        self.out.push_str(text);
+        self.out.push('\n');
+    }
+
+    /// Emits a blank line when the source had one before `line` (at most one is kept).
+    fn separate(&mut self, line: usize) {
+        if let Some(last) = self.last_line
+            && (self.force_blank || line > last + 1)
+        {
+            self.out.push('\n');
+        }
+        self.force_blank = false;
+    }
+
+    fn flush_comments_before(&mut self, line: usize) {
+        while let Some(comment) = self
+            .comments
+            .get(self.next_comment)
+            .filter(|c| c.line < line)
+            .cloned()
+        {
+            self.separate(comment.line);
+            self.write_line(&comment.text);
+            self.last_line = Some(comment.line);
+            self.next_comment += 1;
+        }
+    }
+
+    fn has_comments_before(&self, line: usize) -> bool {
+        self.comments
+            .get(self.next_comment)
+            .is_some_and(|c| c.line < line)
+    }
+
+    /// Writes the last line of a statement, keeping a trailing comment on `end` in place and
+    /// moving comments that sat inside a multi-line statement directly below it.
+    fn finish_line(&mut self, text: &str, end: usize) {
+        let mut trailing = None;
+        let mut interior = Vec::new();
+        while let Some(comment) = self
+            .comments
+            .get(self.next_comment)
+            .filter(|c| c.line <= end)
+            .cloned()
+        {
+            if comment.trailing && comment.line == end && trailing.is_none() {
+                trailing = Some(comment.text);
+            } else {
+                interior.push(comment.text);
+            }
+            self.next_comment += 1;
+        }
+
+        match trailing {
+            Some(comment) => self.write_line(&format!("{} {}", text, comment)),
+            None => self.write_line(text),
+        }
+        for comment in interior {
+            self.write_line(&comment);
+        }
+    }
+
+    fn statement(&mut self, node: &AstNode) -> FormatResult<()> {
+        let start = start_line(node).unwrap_or_else(|| self.last_line.unwrap_or(0));
+        let end = end_line(node).unwrap_or(start);
+        // Spans start after any `@allow(...)`, which is taken to sit on the line above.
+        let first = if node.allow.is_empty() {
+            start
+        } else {
+            start.saturating_sub(1)
+        };
+        self.flush_comments_before(first);
+        self.separate(first);
+        if !node.allow.is_empty() {
+            self.write_line(&format!("@allow({})", node.allow.join(", ")));
+        }
+
+        match node.get_kind() {
+            AstNodeKind::Workspace {
+                name,
+                attributes,
+                body,
+            } => {
+                self.attributes(attributes);
+                self.block_statement(&format!("workspace {}", name), body, end)?;
+            }
+            AstNodeKind::Project {
+                name,
+                attributes,
+                body,
+            } => {
+                self.attributes(attributes);
+                self.block_statement(&format!("project {}", name), body, end)?;
+            }
+            AstNodeKind::Stage {
+                name,
+                attributes,
+                args,
+                body,
+            } => {
+                self.attributes(attributes);
+                let params = match args {
+                    Some(args) => args
+                        .children()
+                        .into_iter()
+                        .map(|a| self.expr(a, self.indent))
+                        .collect::<FormatResult<Vec<_>>>()?
+                        .join(", "),
+                    None => String::new(),
+                };
+                self.block_statement(&format!("stage {}({})", name, params), body, end)?;
+            }
+            AstNodeKind::Block { .. } => self.block_statement("", node, end)?,
+            AstNodeKind::Handler { event, body } => self.block_statement(event, body, end)?,
+            AstNodeKind::If { condition, body } => {
+                let header = format!("if {}", self.expr(condition, self.indent)?);
+                self.block_statement(&header, body, end)?;
+            }
+            AstNodeKind::IfElse {
+                condition,
+                if_body,
+                else_body,
+            } => {
+                let header = format!("if {} {{", self.expr(condition, self.indent)?);
+                self.write_line(&header);
+                self.block_body(if_body)?;
+                self.write_line("} else {");
+                self.block_body(else_body)?;
+                self.finish_line("}", end);
+            }
+            AstNodeKind::ForIn {
+                iterator,
+                iterable,
+                body,
+            } => {
+                let header = format!("for {} in {}", iterator, self.expr(iterable, self.indent)?);
+                self.block_statement(&header, body, end)?;
+            }
+            AstNodeKind::ForTo {
+                initializer,
+                limit,
+                body,
+            } => {
+                let header = format!(
+                    "for {} to {}",
+                    self.expr(initializer, self.indent)?,
+                    self.expr(limit, self.indent)?
+                );
+                self.block_statement(&header, body, end)?;
+            }
+            AstNodeKind::Retry {
+                attempts,
+                backoff,
+                body,
+            } => {
+                let mut header = format!("retry ({}", self.expr(attempts, self.indent)?);
+                if let Some(backoff) = backoff {
+                    header.push_str(&format!(", backoff={}", self.expr(backoff, self.indent)?));
+                }
+                header.push(')');
+                self.block_statement(&header, body, end)?;
+            }
+            AstNodeKind::Timeout { duration, body } => {
+                let header = format!("timeout ({})", self.expr(duration, self.indent)?);
+                self.block_statement(&header, body, end)?;
+            }
+            AstNodeKind::Try {
+                body,
+                binding,
+                handler,
+            } => {
+                self.write_line("try {");
+                self.block_body(body)?;
+                self.write_line(&format!("}} catch ({}) {{", binding));
+                self.block_body(handler)?;
+                self.finish_line("}", end);
+            }
+            AstNodeKind::While { condition, body } => {
+                let header = format!("while {}", self.expr(condition, self.indent)?);
+                self.block_statement(&header, body, end)?;
+            }
+            AstNodeKind::Import { module, alias } => {
+                self.finish_line(&format!("import \"{}\" as {};", module, alias), end);
+            }
+            AstNodeKind::Include { file } => {
+                self.finish_line(&format!("include \"{}\";", file), end);
+            }
+            AstNodeKind::Return { value: Some(value) } => {
+                let text = format!("return {};", self.expr(value, self.indent)?);
+                self.finish_line(&text, end);
+            }
+            AstNodeKind::Identifier { .. }
+            | AstNodeKind::String { .. }
+            | AstNodeKind::Integer { .. }
+            | AstNodeKind::Float { .. }
+            | AstNodeKind::Bool { .. }
+            | AstNodeKind::Null
+            | AstNodeKind::Command { .. }
+            | AstNodeKind::List { .. }
+            | AstNodeKind::UnaryOp { .. }
+            | AstNodeKind::PostfixOp { .. }
+            | AstNodeKind::BinaryOp { .. }
+            | AstNodeKind::Call { .. }
+            | AstNodeKind::Member { .. }
+            | AstNodeKind::Index { .. }
+            | AstNodeKind::Assignment { .. } => {
+                let text = format!("{};", self.expr(node, self.indent)?);
+                self.finish_line(&text, end);
+            }
+            // The grammar requires a value after `return`.
+            AstNodeKind::Return { value: None } => {
+                return Err(FormatError::boxed(
+                    "Cannot format a return without a value.",
+                    node,
+                ));
+            }
+            // Never produced as statements by the parser.
+            AstNodeKind::Statement | AstNodeKind::Script { .. } | AstNodeKind::Arguments { .. } => {
+                return Err(FormatError::boxed(
+                    "Cannot format this node as a statement.",
+                    node,
+                ));
+            }
+        }
+
+        self.last_line = Some(end);
+        Ok(())
+    }
+
+    fn attributes(&mut self, attributes: &[String]) {
+        if !attributes.is_empty() {
+            self.write_line(&format!("[{}]", attributes.join(", ")));
+        }
+    }
+
+    /// Writes `header { ... }`, collapsing to `header {}` when the block has no content.
+    fn block_statement(&mut self, header: &str, block: &AstNode, end: usize) -> FormatResult<()> {
+        let open = if header.is_empty() {
+            "{".to_string()
+        } else {
+            format!("{} {{", header)
+        };
+
+        let is_empty = match block.get_kind() {
+            AstNodeKind::Block { statements } => statements.is_empty(),
+            _ => false,
+        };
+        if is_empty && !self.has_comments_before(end) {
+            self.finish_line(&format!("{}}}", open), end);
+            return Ok(());
+        }
+
+        self.write_line(&open);
+        self.block_body(block)?;
+        self.finish_line("}", end);
+        Ok(())
+    }
+
+    fn block_body(&mut self, block: &AstNode) -> FormatResult<()> {
+        let close_line = end_line(block).unwrap_or(usize::MAX);
+        let saved_last_line = self.last_line.take();
+        self.indent += 1;
+        match block.get_kind() {
+            AstNodeKind::Block { statements } => {
+                for statement in statements {
+                    self.statement(statement)?;
+                }
+            }
+            _ => self.statement(block)?,
+        }
+        self.flush_comments_before(close_line);
+        self.indent -= 1;
+        self.last_line = saved_last_line;
+        Ok(())
+    }
+
+    /// Renders `node`, wrapping it in parentheses when it binds looser than `min_precedence`.
+    fn operand(&self, node: &AstNode, min_precedence: u8, indent: usize) -> FormatResult<String> {
+        let text = self.expr(node, indent)?;
+        if precedence(node) < min_precedence {
+            Ok(format!("({})", text))
+        } else {
+            Ok(text)
+        }
+    }
+
+    fn expr(&self, node: &AstNode, indent: usize) -> FormatResult<String> {
+        let text = match node.get_kind() {
+            AstNodeKind::Identifier { name } => name.clone(),
+            AstNodeKind::String { value } => value.clone(),
+            AstNodeKind::Integer { value } => value.to_string(),
+            AstNodeKind::Float { value } => self.float(node, *value),
+            AstNodeKind::Bool { value } => value.to_string(),
+            AstNodeKind::Null => "null".to_string(),
+            AstNodeKind::Command { name, arg } => format!("{} {}", name, arg),
+            AstNodeKind::List { elements } => self.sequence("[", elements, "]", indent, false)?,
+            AstNodeKind::UnaryOp { op, expr } => {
+                let operand = self.operand(expr, 5, indent)?;
+                // Keep `- -x` from collapsing into the `--` operator.
+                let needs_space = match (op.chars().last(), operand.chars().next()) {
+                    (Some(a), Some(b)) => a == b && (a == '+' || a == '-'),
+                    _ => false,
+                };
+                if needs_space {
+                    format!("{} {}", op, operand)
+                } else {
+                    format!("{}{}", op, operand)
+                }
+            }
+            AstNodeKind::PostfixOp { op, expr } => {
+                format!("{}{}", self.operand(expr, 6, indent)?, op)
+            }
+            AstNodeKind::BinaryOp { left, op, right } => {
+                let own = precedence(node);
+                format!(
+                    "{} {} {}",
+                    self.operand(left, own, indent)?,
+                    op,
+                    self.operand(right, own + 1, indent)?
+                )
+            }
+            AstNodeKind::Call { callee, args } => format!(
+                "{}{}",
+                self.operand(callee, 6, indent)?,
+                self.sequence("(", args, ")", indent, true)?
+            ),
+            AstNodeKind::Member { object, member } => {
+                format!("{}.{}", self.operand(object, 6, indent)?, member)
+            }
+            AstNodeKind::Index { object, index } => format!(
+                "{}[{}]",
+                self.operand(object, 6, indent)?,
+                self.expr(index, indent)?
+            ),
+            AstNodeKind::Assignment { target, value } => {
+                self.assignment(node, target, value, indent)?
+            }
+            AstNodeKind::Arguments { args } => args
+                .iter()
+                .map(|a| self.expr(a, indent))
+                .collect::<FormatResult<Vec<_>>>()?
+                .join(", "),
+            // Statements and declarations never appear inside an expression.
+            AstNodeKind::Script { .. }
+            | AstNodeKind::Import { .. }
+            | AstNodeKind::Include { .. }
+            | AstNodeKind::Statement
+            | AstNodeKind::Workspace { .. }
+            | AstNodeKind::Project { .. }
+            | AstNodeKind::Stage { .. }
+            | AstNodeKind::Block { .. }
+            | AstNodeKind::Handler { .. }
+            | AstNodeKind::If { .. }
+            | AstNodeKind::IfElse { .. }
+            | AstNodeKind::ForIn { .. }
+            | AstNodeKind::ForTo { .. }
+            | AstNodeKind::While { .. }
+            | AstNodeKind::Retry { .. }
+            | AstNodeKind::Timeout { .. }
+            | AstNodeKind::Try { .. }
+            | AstNodeKind::Return { .. } => {
+                return Err(FormatError::boxed(
+                    "Cannot format a statement or declaration inside an expression.",
+                    node,
+                ));
+            }
+        };
+        Ok(text)
+    }
+
+    /// Prints a float as written when the source is available, and otherwise in plain
+    /// decimal notation: the grammar has no exponents, so `1e20` would not parse back.
+    fn float(&self, node: &AstNode, value: f64) -> String {
+        if let Some(text) = self.source_text(node)
+            && !text.is_empty()
+            && text.chars().all(|c| c.is_ascii_digit() || c == '.')
+            && text.parse::<f64>() == Ok(value)
+        {
+            return text;
+        }
+        let text = value.to_string();
+        if text.contains('.') {
+            text
+        } else {
+            format!("{}.0", text)
+        }
+    }
+
+    /// The source text covered by a single-line node's span.
+    fn source_text(&self, node: &AstNode) -> Option<String> {
+        let span = node.get_span()?;
+        if span.start.line != span.end.line || span.end.column < span.start.column {
+            return None;
+        }
+        let line = self.source.lines().nth(span.start.line.checked_sub(1)?)?;
+        Some(
+            line.chars()
+                .skip(span.start.column.checked_sub(1)?)
+                .take(span.end.column - span.start.column)
+                .collect(),
+        )
+    }
+
+    fn assignment(
+        &self,
+        node: &AstNode,
+        target: &AstNode,
+        value: &AstNode,
+        indent: usize,
+    ) -> FormatResult<String> {
+        // `x += 1` is desugared to `x = x + 1` by the parser; the synthesized BinaryOp reuses the
+        // statement's span, which is how it is told apart from a hand-written `x = x + 1`.
+        if let AstNodeKind::BinaryOp { left, op, right } = value.get_kind()
+            && value.get_span().is_some()
+            && value.get_span() == node.get_span()
+            && left.get_kind() == target.get_kind()
+        {
+            return Ok(format!(
+                "{} {}= {}",
+                self.expr(target, indent)?,
+                op,
+                self.expr(right, indent)?
+            ));
+        }
+        Ok(format!(
+            "{} = {}",
+            self.expr(target, indent)?,
+            self.expr(value, indent)?
+        ))
+    }
+
+    /// Renders a bracketed, comma separated list, breaking it one item per line when it
+    /// does not fit within the configured width.
+    fn sequence(
+        &self,
+        open: &str,
+        items: &[AstNode],
+        close: &str,
+        indent: usize,
+        trailing_comma: bool,
+    ) -> FormatResult<String> {
+        let inline = items
+            .iter()
+            .map(|item| self.expr(item, indent))
+            .collect::<FormatResult<Vec<_>>>()?
+            .join(", ");
+        let flat = format!("{}{}{}", open, inline, close);
+        let width = indent * self.options.indent_width + flat.len();
+        if items.is_empty() || (width <= self.options.max_width && !flat.contains('\n')) {
+            return Ok(flat);
+        }
+
+        let pad = " ".repeat((indent + 1) * self.options.indent_width);
+        let mut out = format!("{}\n", open);
+        for (i, item) in items.iter().enumerate() {
+            out.push_str(&pad);
+            out.push_str(&self.expr(item, indent + 1)?);
+            if i + 1 < items.len() || trailing_comma {
+                out.push(',');
+            }
+            out.push('\n');
+        }
+        out.push_str(&" ".repeat(indent * self.options.indent_width));
+        out.push_str(close);
+        Ok(out)
+    }
+}
+
+/// A node the formatter cannot print, e.g. a `return` without a value in a hand-built AST.
+/// Formatting fails rather than dropping the code.
+#[derive(Debug, Clone)]
+pub struct FormatError {
+    message: String,
+    location: Option<Location>,
+    span: Option<Span>,
+}
+
+impl FormatError {
+    fn boxed(message: &str, node: &AstNode) -> Box<dyn MainstageErrorExt> {
+        Box::new(FormatError {
+            message: message.to_string(),
+            location: node.location.clone(),
+            span: node.span.clone(),
+        })
+    }
+}
+
+impl std::fmt::Display for FormatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+impl MainstageErrorExt for FormatError {
+    fn level(&self) -> Level {
+        Level::Error
+    }
+
+    fn message(&self) -> String {
+        self.message.clone()
+    }
+
+    fn issuer(&self) -> String {
+        "mainstage.formatter".to_string()
+    }
+
+    fn span(&self) -> Option<Span> {
+        self.span.clone()
+    }
+
+    fn location(&self) -> Option<Location> {
+        self.location.clone()
+    }
+}
@@ -1,10 +1,19 @@
+pub mod analyzer;
 pub mod ast;
+pub mod config;
 pub mod error;
+pub mod fingerprint;
+pub mod formatter;
 pub mod location;
+pub mod prelude;
+pub mod project;
 pub mod script;
+pub mod testing;
 
 pub use ast::RulesParser;
+pub use config::Config;
 pub use error::{Level, MainstageErrorExt};
+pub use formatter::{FormatOptions, format_script};
 pub use location::{Location, Span};
 pub use script::Script;
 
@@ -36,7 +45,7 @@ pub fn optimize_ir(ir: &str) -> Result<String, Box<dyn MainstageErrorExt>> {
 }
 
 pub fn run_ir_in_vm(_ir: &str) -> Result<String, Box<dyn MainstageErrorExt>> {
-    Ok(format!("IR"))
+    Ok("IR".to_string())
 }
 
 pub fn compile_source_to_ir(source: &Script) -> Result<String, Box<dyn MainstageErrorExt>> {
@@ -1,5 +1,6 @@
+use serde::Serialize;
 
-#[derive(Debug, Clone, PartialEq, Eq, Default, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Default, Hash, Serialize)]
 pub struct Location {
     /// The file in which the location is found.
     pub file: String,
@@ -22,7 +23,7 @@ impl std::fmt::Display for Location {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Default, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Default, Hash, Serialize)]
 pub struct Span {
     /// The starting location of the span.
     pub start: Location,
@@ -0,0 +1,12 @@
+//! The supported public surface of `mainstage_core`.
+//!
+//! Embedders should import from here (`use mainstage_core::prelude::*;`). Everything
+//! re-exported below follows semver; other paths may move between minor versions.
+
+pub use crate::ast::{AstNode, AstNodeKind, generate_ast_from_source};
+pub use crate::config::{Config, UserConfig};
+pub use crate::error::{Level, MainstageErrorExt};
+pub use crate::formatter::{FormatOptions, format_script, format_script_with};
+pub use crate::generate_error_report;
+pub use crate::location::{Location, Span};
+pub use crate::script::Script;
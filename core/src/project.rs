@@ -0,0 +1,233 @@
+//! Sets of scripts built together: the files given to `build`, the scripts found in a
+//! directory, and everything they pull in with `include`.
+
+use std::path::{Path, PathBuf};
+
+use crate::ast::{AstNode, AstNodeKind, generate_ast_from_source};
+use crate::config::{CONFIG_FILE_NAME, Config};
+use crate::error::{Level, MainstageErrorExt};
+use crate::location::{Location, Span};
+use crate::script::Script;
+
+/// Extension of Mainstage scripts.
+pub const SCRIPT_EXTENSION: &str = "ms";
+
+/// A script in a project and its parsed AST.
+#[derive(Debug, Clone)]
+pub struct SourceFile {
+    pub script: Script,
+    pub ast: AstNode,
+}
+
+/// Every script in a multi-file build. Roots come in the order given, each followed by
+/// the files it includes that have not been loaded already.
+#[derive(Debug, Clone, Default)]
+pub struct Project {
+    pub files: Vec<SourceFile>,
+}
+
+impl Project {
+    /// Parses `roots` and, transitively, every file they include, resolving `include`
+    /// paths against the including file's directory. Each file is loaded once. Errors do
+    /// not stop loading, so every broken file is reported in one pass.
+    pub fn load(roots: &[PathBuf]) -> (Project, Vec<Box<dyn MainstageErrorExt>>) {
+        let mut project = Project::default();
+        let mut errors = Vec::new();
+        let mut seen: Vec<PathBuf> = Vec::new();
+        let mut pending: Vec<PathBuf> = Vec::new();
+
+        for root in roots {
+            pending.push(root.clone());
+            while let Some(path) = pending.pop() {
+                let key = std::fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+                if seen.contains(&key) {
+                    continue;
+                }
+                seen.push(key);
+
+                let script = match Script::new(path.clone()) {
+                    Ok(script) => script,
+                    Err(e) => {
+                        errors.push(e);
+                        continue;
+                    }
+                };
+                let ast = match generate_ast_from_source(&script) {
+                    Ok(ast) => ast,
+                    Err(e) => {
+                        errors.push(e);
+                        continue;
+                    }
+                };
+
+                let base = path.parent().unwrap_or_else(|| Path::new(""));
+                let mut includes = Vec::new();
+                collect_includes(&ast, &mut includes);
+                // Pushed in reverse so that includes load in source order.
+                for (file, node) in includes.into_iter().rev() {
+                    let target = base.join(file);
+                    if target.is_file() {
+                        pending.push(target);
+                    } else {
+                        errors.push(ProjectError::boxed(
+                            format!("Included file '{}' was not found.", file),
+                            "mainstage.project.include",
+                            node,
+                        ));
+                    }
+                }
+                project.files.push(SourceFile { script, ast });
+            }
+        }
+        errors.extend(project.duplicate_declarations());
+        (project, errors)
+    }
+
+    /// Workspaces and stages declared under the same name in more than one file, which
+    /// would be ambiguous once the files are linked together.
+    fn duplicate_declarations(&self) -> Vec<Box<dyn MainstageErrorExt>> {
+        let mut declared: Vec<(&str, &str, &Path)> = Vec::new();
+        let mut errors = Vec::new();
+        for file in &self.files {
+            for item in file.ast.children() {
+                let (kind, name) = match item.get_kind() {
+                    AstNodeKind::Workspace { name, .. } => ("Workspace", name),
+                    AstNodeKind::Stage { name, .. } => ("Stage", name),
+                    _ => continue,
+                };
+                let earlier = declared
+                    .iter()
+                    .find(|(k, n, path)| *k == kind && *n == name && *path != file.script.path);
+                match earlier {
+                    Some((_, _, path)) => errors.push(ProjectError::boxed(
+                        format!(
+                            "{} '{}' is already declared in {}.",
+                            kind,
+                            name,
+                            path.display()
+                        ),
+                        "mainstage.project.duplicate",
+                        item,
+                    )),
+                    None => declared.push((kind, name, &file.script.path)),
+                }
+            }
+        }
+        errors
+    }
+}
+
+/// Expands a build input into the scripts to load. A script is used as is; a directory
+/// (or a `mainstage.toml` in one) uses the configured `script` when there is one, and
+/// otherwise every `.ms` file directly inside it, sorted by name.
+pub fn resolve_input(input: &Path) -> Result<Vec<PathBuf>, Box<dyn MainstageErrorExt>> {
+    let dir = if input.is_dir() {
+        input
+    } else if input
+        .file_name()
+        .is_some_and(|name| name == CONFIG_FILE_NAME)
+    {
+        input.parent().unwrap_or_else(|| Path::new(""))
+    } else {
+        return Ok(vec![input.to_path_buf()]);
+    };
+
+    let config_path = dir.join(CONFIG_FILE_NAME);
+    if config_path.is_file()
+        && let Some(script) = Config::load(&config_path)?.script
+    {
+        return Ok(vec![script]);
+    }
+
+    let entries = std::fs::read_dir(dir).map_err(|e| {
+        ProjectError::boxed_unlocated(
+            format!("Could not read {}: {}.", dir.display(), e),
+            "mainstage.project.resolve_input",
+        )
+    })?;
+    let mut scripts: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.is_file() && path.extension().is_some_and(|ext| ext == SCRIPT_EXTENSION)
+        })
+        .collect();
+    scripts.sort();
+    if scripts.is_empty() {
+        return Err(ProjectError::boxed_unlocated(
+            format!(
+                "No .{} scripts found in {}.",
+                SCRIPT_EXTENSION,
+                dir.display()
+            ),
+            "mainstage.project.resolve_input",
+        ));
+    }
+    Ok(scripts)
+}
+
+fn collect_includes<'a>(node: &'a AstNode, out: &mut Vec<(&'a str, &'a AstNode)>) {
+    if let AstNodeKind::Include { file } = node.get_kind() {
+        out.push((file, node));
+    }
+    for child in node.children() {
+        collect_includes(child, out);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ProjectError {
+    message: String,
+    issuer: &'static str,
+    location: Option<Location>,
+    span: Option<Span>,
+}
+
+impl ProjectError {
+    fn boxed(message: String, issuer: &'static str, node: &AstNode) -> Box<dyn MainstageErrorExt> {
+        Box::new(ProjectError {
+            message,
+            issuer,
+            location: node.location.clone(),
+            span: node.span.clone(),
+        })
+    }
+
+    fn boxed_unlocated(message: String, issuer: &'static str) -> Box<dyn MainstageErrorExt> {
+        Box::new(ProjectError {
+            message,
+            issuer,
+            location: None,
+            span: None,
+        })
+    }
+}
+
+impl std::fmt::Display for ProjectError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for ProjectError {}
+
+impl MainstageErrorExt for ProjectError {
+    fn level(&self) -> Level {
+        Level::Error
+    }
+
+    fn message(&self) -> String {
+        self.message.clone()
+    }
+
+    fn issuer(&self) -> String {
+        self.issuer.to_string()
+    }
+
+    fn span(&self) -> Option<Span> {
+        self.span.clone()
+    }
+
+    fn location(&self) -> Option<Location> {
+        self.location.clone()
+    }
+}
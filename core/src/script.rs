@@ -26,6 +26,20 @@ impl Script {
         })
     }
 
+    /// Creates a script from in-memory content, such as an unsaved editor buffer.
+    pub fn from_source(path: PathBuf, content: String) -> Self {
+        let name = path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        Script {
+            name,
+            path,
+            content,
+        }
+    }
+
     pub fn is_empty(&self) -> bool {
         self.content.trim().is_empty()
     }
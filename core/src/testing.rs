@@ -0,0 +1,154 @@
+//! Helpers for golden-file tests of compiler output.
+//!
+//! Renderings here are deterministic: node IDs are replaced by their pre-order position
+//! in the tree, so the same source always produces the same text regardless of how many
+//! nodes were created before it. Snapshots are rewritten instead of compared when the
+//! `MAINSTAGE_UPDATE_SNAPSHOTS` environment variable is set.
+
+use std::path::Path;
+
+use crate::ast::{AstNode, AstNodeKind};
+
+/// Environment variable that makes [`assert_snapshot`] write snapshots instead of comparing.
+pub const UPDATE_SNAPSHOTS_ENV: &str = "MAINSTAGE_UPDATE_SNAPSHOTS";
+
+/// Renders an AST as an indented tree with stable IDs, one node per line:
+/// `#<id> <Kind> <fields> @<start line>:<col>-<end line>:<col>`.
+pub fn render_ast(root: &AstNode) -> String {
+    let mut out = String::new();
+    let mut next_id = 0;
+    render_node(root, 0, &mut next_id, &mut out);
+    out
+}
+
+fn render_node(node: &AstNode, depth: usize, next_id: &mut usize, out: &mut String) {
+    let (kind, fields) = describe(node.get_kind());
+    out.push_str(&"  ".repeat(depth));
+    out.push_str(&format!("#{} {}", next_id, kind));
+    *next_id += 1;
+    for (name, value) in fields {
+        out.push_str(&format!(" {}={}", name, value));
+    }
+    if !node.allow.is_empty() {
+        out.push_str(&format!(" allow=[{}]", node.allow.join(", ")));
+    }
+    if let Some(span) = node.get_span() {
+        out.push_str(&format!(
+            " @{}:{}-{}:{}",
+            span.start.line, span.start.column, span.end.line, span.end.column
+        ));
+    }
+    out.push('\n');
+    for child in node.children() {
+        render_node(child, depth + 1, next_id, out);
+    }
+}
+
+/// Returns the kind name and the non-node fields of a node; child nodes are rendered
+/// separately via [`AstNode::children`].
+fn describe(kind: &AstNodeKind) -> (&'static str, Vec<(&'static str, String)>) {
+    match kind {
+        AstNodeKind::Script { .. } => ("Script", vec![]),
+        AstNodeKind::Import { module, alias } => (
+            "Import",
+            vec![("module", quote(module)), ("alias", alias.clone())],
+        ),
+        AstNodeKind::Include { file } => ("Include", vec![("file", quote(file))]),
+        AstNodeKind::Statement => ("Statement", vec![]),
+        AstNodeKind::Arguments { .. } => ("Arguments", vec![]),
+        AstNodeKind::Workspace {
+            name, attributes, ..
+        } => ("Workspace", declaration_fields(name, attributes)),
+        AstNodeKind::Project {
+            name, attributes, ..
+        } => ("Project", declaration_fields(name, attributes)),
+        AstNodeKind::Stage {
+            name, attributes, ..
+        } => ("Stage", declaration_fields(name, attributes)),
+        AstNodeKind::Block { .. } => ("Block", vec![]),
+        AstNodeKind::Handler { event, .. } => ("Handler", vec![("event", event.clone())]),
+        AstNodeKind::If { .. } => ("If", vec![]),
+        AstNodeKind::IfElse { .. } => ("IfElse", vec![]),
+        AstNodeKind::ForIn { iterator, .. } => ("ForIn", vec![("iterator", iterator.clone())]),
+        AstNodeKind::ForTo { .. } => ("ForTo", vec![]),
+        AstNodeKind::While { .. } => ("While", vec![]),
+        AstNodeKind::Retry { backoff, .. } => {
+            ("Retry", vec![("backoff", backoff.is_some().to_string())])
+        }
+        AstNodeKind::Timeout { .. } => ("Timeout", vec![]),
+        AstNodeKind::Try { binding, .. } => ("Try", vec![("binding", binding.clone())]),
+        AstNodeKind::UnaryOp { op, .. } => ("UnaryOp", vec![("op", op.clone())]),
+        AstNodeKind::PostfixOp { op, .. } => ("PostfixOp", vec![("op", op.clone())]),
+        AstNodeKind::BinaryOp { op, .. } => ("BinaryOp", vec![("op", op.clone())]),
+        AstNodeKind::Assignment { .. } => ("Assignment", vec![]),
+        AstNodeKind::Command { name, arg } => {
+            ("Command", vec![("name", name.clone()), ("arg", quote(arg))])
+        }
+        AstNodeKind::Call { .. } => ("Call", vec![]),
+        AstNodeKind::Member { member, .. } => ("Member", vec![("member", member.clone())]),
+        AstNodeKind::Index { .. } => ("Index", vec![]),
+        AstNodeKind::Return { .. } => ("Return", vec![]),
+        AstNodeKind::Identifier { name } => ("Identifier", vec![("name", name.clone())]),
+        AstNodeKind::String { value } => ("String", vec![("value", quote(value))]),
+        AstNodeKind::Integer { value } => ("Integer", vec![("value", value.to_string())]),
+        AstNodeKind::Float { value } => ("Float", vec![("value", format!("{:?}", value))]),
+        AstNodeKind::Bool { value } => ("Bool", vec![("value", value.to_string())]),
+        AstNodeKind::List { .. } => ("List", vec![]),
+        AstNodeKind::Null => ("Null", vec![]),
+    }
+}
+
+fn declaration_fields(name: &str, attributes: &[String]) -> Vec<(&'static str, String)> {
+    let mut fields = vec![("name", name.to_string())];
+    if !attributes.is_empty() {
+        fields.push(("attributes", format!("[{}]", attributes.join(", "))));
+    }
+    fields
+}
+
+fn quote(value: &str) -> String {
+    format!("{:?}", value.trim_matches('"'))
+}
+
+/// Compares `actual` against the snapshot file at `path`, panicking with the first
+/// differing line on mismatch. A missing snapshot fails the assertion so that a deleted
+/// golden file is caught; with `MAINSTAGE_UPDATE_SNAPSHOTS` set, snapshots are written or
+/// overwritten instead of compared.
+pub fn assert_snapshot(path: impl AsRef<Path>, actual: &str) {
+    let path = path.as_ref();
+    let update = std::env::var_os(UPDATE_SNAPSHOTS_ENV).is_some_and(|v| !v.is_empty());
+    if update {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).expect("failed to create snapshot directory");
+        }
+        std::fs::write(path, actual).expect("failed to write snapshot");
+        return;
+    }
+    let Ok(expected) = std::fs::read_to_string(path) else {
+        panic!(
+            "snapshot {} is missing. Set {}=1 to create it.",
+            path.display(),
+            UPDATE_SNAPSHOTS_ENV
+        );
+    };
+    if expected == actual {
+        return;
+    }
+
+    let mismatch = expected
+        .lines()
+        .zip(actual.lines())
+        .position(|(e, a)| e != a)
+        .unwrap_or_else(|| expected.lines().count().min(actual.lines().count()));
+    panic!(
+        "snapshot {} does not match at line {}\n  expected: {}\n    actual: {}\nSet {}=1 to update it.",
+        path.display(),
+        mismatch + 1,
+        expected
+            .lines()
+            .nth(mismatch)
+            .unwrap_or("<end of snapshot>"),
+        actual.lines().nth(mismatch).unwrap_or("<end of output>"),
+        UPDATE_SNAPSHOTS_ENV,
+    );
+}
@@ -0,0 +1,77 @@
+//! Golden tests for the parser: every e2e sample and bundled example is rendered with
+//! `render_ast` and compared against `tests/snapshots/<path>.ast`, where `<path>` is the
+//! script's path below its samples directory with `/` replaced by `_`. Run with
+//! `MAINSTAGE_UPDATE_SNAPSHOTS=1` to accept intended changes.
+
+use std::path::{Path, PathBuf};
+
+use mainstage_core::prelude::*;
+use mainstage_core::testing::{assert_snapshot, render_ast};
+
+fn repo_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .expect("core lives inside the repository")
+        .to_path_buf()
+}
+
+/// The `.ms` files directly inside `dir`, sorted by name.
+fn scripts_in(dir: &Path) -> Vec<PathBuf> {
+    let mut scripts: Vec<PathBuf> = std::fs::read_dir(dir)
+        .unwrap_or_else(|e| panic!("cannot read {}: {}", dir.display(), e))
+        .map(|entry| entry.expect("readable directory entry").path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "ms"))
+        .collect();
+    scripts.sort();
+    scripts
+}
+
+fn check(script_path: &Path, snapshot_name: &str) {
+    let script = Script::new(script_path.to_path_buf()).expect("script is readable");
+    let ast = generate_ast_from_source(&script)
+        .unwrap_or_else(|e| panic!("{} does not parse: {}", script_path.display(), e));
+    let snapshot = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(format!("{}.ast", snapshot_name));
+    assert_snapshot(snapshot, &render_ast(&ast));
+}
+
+fn stem(path: &Path) -> String {
+    path.file_stem().unwrap().to_string_lossy().to_string()
+}
+
+#[test]
+fn e2e_samples() {
+    let scripts = scripts_in(&repo_root().join("cli/samples/e2e"));
+    assert!(!scripts.is_empty());
+    for script in scripts {
+        check(&script, &format!("e2e_{}", stem(&script)));
+    }
+}
+
+/// The `.ms` files anywhere under `dir`, sorted by path.
+fn scripts_under(dir: &Path) -> Vec<PathBuf> {
+    let mut scripts = scripts_in(dir);
+    let mut subdirs: Vec<PathBuf> = std::fs::read_dir(dir)
+        .unwrap_or_else(|e| panic!("cannot read {}: {}", dir.display(), e))
+        .map(|entry| entry.expect("readable directory entry").path())
+        .filter(|path| path.is_dir())
+        .collect();
+    subdirs.sort();
+    for subdir in subdirs {
+        scripts.extend(scripts_under(&subdir));
+    }
+    scripts
+}
+
+#[test]
+fn examples() {
+    let root = repo_root().join("cli/examples");
+    let scripts = scripts_under(&root);
+    assert!(!scripts.is_empty());
+    for script in scripts {
+        let relative = script.strip_prefix(&root).unwrap().with_extension("");
+        let name = relative.to_string_lossy().replace(['/', '\\'], "_");
+        check(&script, &name);
+    }
+}
@@ -0,0 +1,120 @@
+use std::path::PathBuf;
+
+use mainstage_core::analyzer::{CallGraph, GraphNodeKind};
+use mainstage_core::prelude::*;
+
+fn graph(source: &str) -> CallGraph {
+    let script = Script::from_source(PathBuf::from("test.ms"), source.to_string());
+    let ast = generate_ast_from_source(&script).expect("source should parse");
+    CallGraph::from_ast(&ast)
+}
+
+fn edges(graph: &CallGraph) -> Vec<(&str, &str, Option<&str>)> {
+    graph
+        .edges
+        .iter()
+        .map(|e| (e.from.as_str(), e.to.as_str(), e.function.as_deref()))
+        .collect()
+}
+
+#[test]
+fn nodes_are_declarations_in_source_order() {
+    let graph =
+        graph("import \"tools\" as tools;\nworkspace main {}\nstage build() {}\nproject app {}\n");
+    let nodes: Vec<_> = graph
+        .nodes
+        .iter()
+        .map(|n| (n.id.as_str(), n.name.as_str(), n.kind))
+        .collect();
+    assert_eq!(
+        nodes,
+        [
+            ("plugin:tools", "tools", GraphNodeKind::Plugin),
+            ("workspace:main", "main", GraphNodeKind::Workspace),
+            ("stage:build", "build", GraphNodeKind::Stage),
+        ]
+    );
+    assert_eq!(graph.nodes[2].span.as_ref().map(|s| s.start.line), Some(3));
+}
+
+#[test]
+fn calls_become_edges_once_each() {
+    let graph = graph(
+        "workspace main { build(); test(); build(); }\n\
+         stage build() { compile(); }\n\
+         stage compile() {}\n\
+         stage test() { if true { compile(); } }\n",
+    );
+    assert_eq!(
+        edges(&graph),
+        [
+            ("workspace:main", "stage:build", None),
+            ("workspace:main", "stage:test", None),
+            ("stage:build", "stage:compile", None),
+            ("stage:test", "stage:compile", None),
+        ]
+    );
+    assert_eq!(graph.edges[0].span.as_ref().map(|s| s.start.line), Some(1));
+}
+
+#[test]
+fn recursion_is_an_edge_to_itself() {
+    let graph = graph(
+        "workspace main { walk(); }\n\
+         stage walk() { walk(); ping(); }\n\
+         stage ping() { walk(); }\n",
+    );
+    assert_eq!(
+        edges(&graph),
+        [
+            ("workspace:main", "stage:walk", None),
+            ("stage:walk", "stage:walk", None),
+            ("stage:walk", "stage:ping", None),
+            ("stage:ping", "stage:walk", None),
+        ]
+    );
+    assert_eq!(
+        graph.reachable_from("stage:ping"),
+        ["stage:ping", "stage:walk"]
+    );
+}
+
+#[test]
+fn calls_to_unknown_stages_are_left_out() {
+    let graph = graph(
+        "workspace main { missing(); print(\"hi\"); main(); build(); }\n\
+         stage build() { other.run(); }\n",
+    );
+    assert_eq!(edges(&graph), [("workspace:main", "stage:build", None)]);
+    assert!(graph.node("stage:missing").is_none());
+}
+
+#[test]
+fn plugin_calls_are_labelled_with_the_function() {
+    let graph = graph(
+        "import \"tools\" as tools;\n\
+         workspace main { tools.lint(); tools.fmt(); tools.lint(); }\n",
+    );
+    assert_eq!(
+        edges(&graph),
+        [
+            ("workspace:main", "plugin:tools", Some("lint")),
+            ("workspace:main", "plugin:tools", Some("fmt")),
+        ]
+    );
+}
+
+#[test]
+fn reachable_from_skips_unconnected_stages() {
+    let graph = graph(
+        "workspace main { build(); }\n\
+         stage build() { compile(); }\n\
+         stage compile() {}\n\
+         stage clean() {}\n",
+    );
+    assert_eq!(
+        graph.reachable_from("workspace:main"),
+        ["workspace:main", "stage:build", "stage:compile"]
+    );
+    assert_eq!(graph.reachable_from("stage:clean"), ["stage:clean"]);
+}
@@ -0,0 +1,265 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use mainstage_core::analyzer::LintLevel;
+use mainstage_core::prelude::*;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir =
+        std::env::temp_dir().join(format!("mainstage-config-{}-{}", std::process::id(), name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).expect("temp dir is writable");
+    dir
+}
+
+fn parse(content: &str) -> Config {
+    Config::parse(content, Path::new("mainstage.toml")).expect("config should parse")
+}
+
+fn parse_error(content: &str) -> String {
+    Config::parse(content, Path::new("mainstage.toml"))
+        .expect_err("config should be rejected")
+        .message()
+}
+
+#[test]
+fn discover_finds_the_file_in_an_ancestor() {
+    let dir = temp_dir("discover");
+    fs::write(dir.join("mainstage.toml"), "jobs = 2\n").unwrap();
+    let nested = dir.join("a").join("b");
+    fs::create_dir_all(&nested).unwrap();
+
+    let config = Config::discover(&nested)
+        .expect("config should load")
+        .expect("config should be found");
+    assert_eq!(config.jobs, Some(2));
+    assert_eq!(config.path, Some(dir.join("mainstage.toml")));
+}
+
+#[test]
+fn discover_without_a_file_returns_none() {
+    let dir = temp_dir("discover-none");
+    // Only meaningful when no ancestor of the temp dir has a mainstage.toml of its own.
+    if dir
+        .ancestors()
+        .skip(1)
+        .any(|d| d.join("mainstage.toml").is_file())
+    {
+        return;
+    }
+    assert!(Config::discover(&dir).expect("nothing to load").is_none());
+}
+
+#[test]
+fn load_resolves_paths_against_the_file() {
+    let dir = temp_dir("load");
+    let path = dir.join("mainstage.toml");
+    fs::write(
+        &path,
+        "script = \"build.ms\"\nplugin_dirs = [\"plugins\"]\nout_dir = \"out\"\n\
+         [profiles.ci]\nout_dir = \"ci-out\"\n",
+    )
+    .unwrap();
+
+    let config = Config::load(&path).expect("config should load");
+    assert_eq!(config.path, Some(path));
+    assert_eq!(config.script, Some(dir.join("build.ms")));
+    assert_eq!(config.plugin_dirs, [dir.join("plugins")]);
+    assert_eq!(config.out_dir, Some(dir.join("out")));
+    assert_eq!(config.profiles["ci"].out_dir, Some(dir.join("ci-out")));
+}
+
+#[test]
+fn load_reports_a_missing_file() {
+    let path = temp_dir("load-missing").join("mainstage.toml");
+    let err = Config::load(&path).expect_err("file does not exist");
+    assert!(
+        err.message()
+            .starts_with(&format!("Could not read {}: ", path.display())),
+        "{}",
+        err.message()
+    );
+}
+
+#[test]
+fn parse_reads_every_table() {
+    let config = parse(
+        "opt_level = 2\njobs = 4\n\
+         [env]\nRUST_LOG = \"debug\"\n\
+         [lints]\nunused_stage = \"deny\"\n",
+    );
+    assert_eq!(config.opt_level, Some(2));
+    assert_eq!(config.jobs, Some(4));
+    assert_eq!(config.env["RUST_LOG"], "debug");
+    assert_eq!(config.lints["unused_stage"], LintLevel::Deny);
+    assert_eq!(config.path, None);
+}
+
+#[test]
+fn parse_rejects_unknown_fields() {
+    let message = parse_error("jobz = 4\n");
+    assert!(
+        message.starts_with("Invalid mainstage.toml: "),
+        "{}",
+        message
+    );
+    assert!(message.contains("jobz"), "{}", message);
+
+    let message = parse_error("[profiles.ci]\nspeed = 1\n");
+    assert!(message.contains("speed"), "{}", message);
+}
+
+#[test]
+fn parse_errors_point_at_the_bad_value() {
+    let err = Config::parse(
+        "jobs = 1\nopt_level = \"fast\"\n",
+        Path::new("mainstage.toml"),
+    )
+    .expect_err("opt_level must be a number");
+    let span = err.span().expect("toml errors carry a span");
+    assert_eq!(span.start.line, 2);
+    assert_eq!(span.start.column, 13);
+}
+
+#[test]
+fn with_profile_merges_over_the_base() {
+    let config = parse(
+        "opt_level = 1\njobs = 2\nplugin_dirs = [\"base\"]\n\
+         [env]\nMODE = \"dev\"\nKEEP = \"yes\"\n\
+         [profiles.release]\nopt_level = 3\nplugin_dirs = [\"release\"]\n\
+         [profiles.release.env]\nMODE = \"release\"\n",
+    );
+    let release = config.with_profile("release").expect("profile exists");
+    assert_eq!(release.opt_level, Some(3));
+    assert_eq!(release.jobs, Some(2));
+    assert_eq!(release.plugin_dirs, [PathBuf::from("release")]);
+    assert_eq!(release.env["MODE"], "release");
+    assert_eq!(release.env["KEEP"], "yes");
+}
+
+#[test]
+fn with_user_defaults_appends_after_project_settings() {
+    let dir = temp_dir("user-defaults");
+    let user_path = dir.join("config.toml");
+    fs::write(
+        &user_path,
+        "plugin_dirs = [\"/user/plugins\"]\ncache_dir = \"/user/cache\"\n",
+    )
+    .unwrap();
+    let user = UserConfig::load(&user_path).expect("user config should load");
+
+    let config = parse("plugin_dirs = [\"project\"]\n").with_user_defaults(&user);
+    assert_eq!(
+        config.plugin_dirs,
+        [PathBuf::from("project"), PathBuf::from("/user/plugins")]
+    );
+    assert_eq!(config.cache_dir, Some(PathBuf::from("/user/cache")));
+
+    let config = parse("cache_dir = \"project-cache\"\n").with_user_defaults(&user);
+    assert_eq!(config.cache_dir, Some(PathBuf::from("project-cache")));
+}
+
+#[test]
+fn validate_rejects_out_of_range_values() {
+    assert_eq!(
+        parse_error("opt_level = 4\n"),
+        "opt_level must be between 0 and 3, found 4."
+    );
+    assert_eq!(parse_error("jobs = 0\n"), "jobs must be at least 1.");
+    assert_eq!(
+        parse_error("[lints]\nunused_stages = \"deny\"\n"),
+        "Unknown lint 'unused_stages' in [lints]."
+    );
+}
+
+#[test]
+fn with_profile_rejects_an_unknown_profile() {
+    let config = parse("[profiles.ci]\njobs = 1\n");
+    let err = config.with_profile("release").expect_err("no such profile");
+    assert_eq!(err.message(), "Unknown profile 'release'.");
+    assert_eq!(
+        err.location().expect("error has a location").file,
+        "mainstage.toml"
+    );
+}
+
+#[test]
+fn profile_values_override_base_values() {
+    let config = parse(
+        "out_dir = \"out\"\ncache_dir = \"cache\"\njobs = 2\n\
+         [lints]\nunused_stage = \"deny\"\nunused_variable = \"deny\"\n\
+         [profiles.ci]\ncache_dir = \"ci-cache\"\njobs = 8\n\
+         [profiles.ci.lints]\nunused_stage = \"allow\"\n",
+    );
+    let ci = config.with_profile("ci").expect("profile exists");
+    assert_eq!(ci.cache_dir, Some(PathBuf::from("ci-cache")));
+    assert_eq!(ci.jobs, Some(8));
+    assert_eq!(ci.out_dir, Some(PathBuf::from("out")));
+    assert_eq!(ci.lints["unused_stage"], LintLevel::Allow);
+    assert_eq!(ci.lints["unused_variable"], LintLevel::Deny);
+    assert_eq!(ci.profiles, config.profiles);
+}
+
+#[test]
+fn profile_without_plugin_dirs_keeps_the_base_list() {
+    let config = parse("plugin_dirs = [\"base\"]\n[profiles.ci]\njobs = 1\n");
+    let ci = config.with_profile("ci").expect("profile exists");
+    assert_eq!(ci.plugin_dirs, [PathBuf::from("base")]);
+}
+
+#[test]
+fn load_resolves_script_and_cache_dirs() {
+    let dir = temp_dir("load-script");
+    let path = dir.join("mainstage.toml");
+    fs::write(
+        &path,
+        "script = \"ci/build.ms\"\ncache_dir = \"cache\"\n\
+         [env]\nTARGET = \"x86_64\"\n\
+         [profiles.ci]\ncache_dir = \"ci-cache\"\nplugin_dirs = [\"ci-plugins\"]\n",
+    )
+    .unwrap();
+
+    let config = Config::load(&path).expect("config should load");
+    assert_eq!(config.script, Some(dir.join("ci").join("build.ms")));
+    assert_eq!(config.cache_dir, Some(dir.join("cache")));
+    assert_eq!(config.env["TARGET"], "x86_64");
+    let ci = config.with_profile("ci").expect("profile exists");
+    assert_eq!(ci.cache_dir, Some(dir.join("ci-cache")));
+    assert_eq!(ci.plugin_dirs, [dir.join("ci-plugins")]);
+}
+
+#[test]
+fn validate_checks_profiles_too() {
+    assert_eq!(
+        parse_error("[profiles.release]\nopt_level = 9\n"),
+        "profiles.release.opt_level must be between 0 and 3, found 9."
+    );
+    assert_eq!(
+        parse_error("[profiles.ci]\njobs = 0\n"),
+        "profiles.ci.jobs must be at least 1."
+    );
+    assert_eq!(
+        parse_error("[profiles.ci.lints]\nshadowing = \"warn\"\n"),
+        "Unknown lint 'shadowing' in [profiles.ci.lints]."
+    );
+}
+
+#[test]
+fn validate_accepts_bounds_and_groups() {
+    let config = parse(
+        "opt_level = 3\njobs = 1\n\
+         [lints]\nall = \"warn\"\nunused = \"deny\"\n\
+         [profiles.debug]\nopt_level = 0\n",
+    );
+    assert_eq!(config.opt_level, Some(3));
+    assert_eq!(config.lints["unused"], LintLevel::Deny);
+}
+
+#[test]
+fn validate_errors_point_at_the_config_file() {
+    let err = Config::parse("jobs = 0\n", Path::new("project/mainstage.toml"))
+        .expect_err("jobs must be positive");
+    let location = err.location().expect("error has a location");
+    assert_eq!(location.file, "project/mainstage.toml");
+    assert_eq!((location.line, location.column), (1, 1));
+}
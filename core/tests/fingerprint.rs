@@ -0,0 +1,110 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use mainstage_core::fingerprint::{Manifest, Mismatch};
+
+/// A fresh directory under the system temp dir, unique to this test process.
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "mainstage-fingerprint-{}-{}",
+        std::process::id(),
+        name
+    ));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).expect("temp dir is writable");
+    dir
+}
+
+/// Writes `out/app.bin` and `out/app.map` under `root` and a manifest for them at
+/// `root/manifest.json`.
+fn build(root: &Path) -> PathBuf {
+    fs::create_dir_all(root.join("out")).unwrap();
+    fs::write(root.join("out/app.bin"), "binary").unwrap();
+    fs::write(root.join("out/app.map"), "map").unwrap();
+    let mut manifest = Manifest::default();
+    manifest.record(&root.join("out/app.bin")).unwrap();
+    manifest.record(&root.join("out/app.map")).unwrap();
+    let manifest_path = root.join("manifest.json");
+    manifest.save(&manifest_path).unwrap();
+    manifest_path
+}
+
+#[test]
+fn saved_manifest_loads_and_verifies() {
+    let root = temp_dir("round-trip");
+    let manifest_path = build(&root);
+
+    let loaded = Manifest::load(&manifest_path).unwrap();
+    assert_eq!(loaded.version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(loaded.artifacts.len(), 2);
+    assert_eq!(loaded.artifacts[0].path, root.join("out/app.bin"));
+    assert_eq!(loaded.artifacts[0].size, 6);
+    assert_eq!(loaded.verify(), []);
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn paths_under_the_manifest_directory_are_stored_relative() {
+    let root = temp_dir("relative");
+    let manifest_path = build(&root);
+    let json = fs::read_to_string(&manifest_path).unwrap();
+    assert!(json.contains("\"path\": \"out/app.bin\""), "{}", json);
+
+    // Moving the manifest together with its artifacts keeps it valid.
+    let moved = temp_dir("relative-moved");
+    fs::remove_dir_all(&moved).unwrap();
+    fs::rename(&root, &moved).unwrap();
+    let loaded = Manifest::load(&moved.join("manifest.json")).unwrap();
+    assert_eq!(loaded.artifacts[0].path, moved.join("out/app.bin"));
+    assert_eq!(loaded.verify(), []);
+    fs::remove_dir_all(moved).unwrap();
+}
+
+#[test]
+fn relative_record_paths_are_made_absolute() {
+    let root = temp_dir("relative-record");
+    let mut manifest = Manifest::default();
+    manifest.record(Path::new("Cargo.toml")).unwrap();
+    manifest.record(Path::new("./Cargo.toml")).unwrap();
+    assert_eq!(manifest.artifacts.len(), 1);
+    assert!(manifest.artifacts[0].path.is_absolute());
+
+    let manifest_path = root.join("manifest.json");
+    manifest.save(&manifest_path).unwrap();
+    let loaded = Manifest::load(&manifest_path).unwrap();
+    assert_eq!(loaded.artifacts, manifest.artifacts);
+    assert_eq!(loaded.verify(), []);
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn verify_reports_each_kind_of_mismatch() {
+    let root = temp_dir("mismatch");
+    let manifest_path = build(&root);
+    let mut manifest = Manifest::load(&manifest_path).unwrap();
+
+    fs::write(root.join("out/app.bin"), "BINARY").unwrap();
+    fs::write(root.join("out/app.map"), "a longer map").unwrap();
+    let mismatches = manifest.verify();
+    assert!(matches!(&mismatches[0], Mismatch::Changed { path, .. } if path.ends_with("app.bin")));
+    assert_eq!(
+        mismatches[1],
+        Mismatch::Resized {
+            path: root.join("out/app.map"),
+            expected: 3,
+            actual: 12,
+        }
+    );
+
+    fs::remove_file(root.join("out/app.map")).unwrap();
+    // A path below a regular file fails with an error other than NotFound.
+    manifest.artifacts[0].path = root.join("out/app.bin/nested");
+    let mismatches = manifest.verify();
+    assert!(
+        matches!(&mismatches[0], Mismatch::Unreadable { .. }),
+        "{:?}",
+        mismatches
+    );
+    assert_eq!(mismatches[1], Mismatch::Missing(root.join("out/app.map")));
+    fs::remove_dir_all(root).unwrap();
+}
@@ -0,0 +1,187 @@
+use mainstage_core::prelude::*;
+use std::path::PathBuf;
+
+fn format(source: &str) -> String {
+    format_with(source, &FormatOptions::default())
+}
+
+fn format_with(source: &str, options: &FormatOptions) -> String {
+    let script = Script::from_source(PathBuf::from("test.ms"), source.to_string());
+    format_script_with(&script, options).expect("source should parse")
+}
+
+/// Formatting must parse back and be stable on a second pass.
+fn assert_round_trip(source: &str) -> String {
+    let formatted = format(source);
+    assert_eq!(
+        format(&formatted),
+        formatted,
+        "formatting is not idempotent"
+    );
+    formatted
+}
+
+#[test]
+fn canonical_source_is_unchanged() {
+    let source = "\
+// Build the app.
+workspace main {
+    x = 1; // first value
+    say(x);
+}
+";
+    assert_eq!(assert_round_trip(source), source);
+}
+
+#[test]
+fn spacing_and_indentation_are_normalized() {
+    let source = "workspace main{\nx=1+2;\n      say( x );\n}\n";
+    assert_eq!(
+        assert_round_trip(source),
+        "workspace main {\n    x = 1 + 2;\n    say(x);\n}\n"
+    );
+}
+
+#[test]
+fn comments_stay_in_place() {
+    let source = "\
+// leading
+workspace main {
+    // before the statement
+    x = 1; // trailing
+    say(x);
+    // at the end of the block
+}
+";
+    assert_eq!(assert_round_trip(source), source);
+}
+
+#[test]
+fn comment_inside_multi_line_statement_moves_below_it() {
+    let source = "\
+workspace main {
+    say(1, // inside
+        2);
+}
+";
+    assert_eq!(
+        assert_round_trip(source),
+        "workspace main {\n    say(1, 2);\n    // inside\n}\n"
+    );
+}
+
+#[test]
+fn compound_assignment_is_kept() {
+    let source = "workspace main {\n    x = 1;\n    x += 2;\n    x = x + 3;\n    say(x);\n}\n";
+    assert_eq!(assert_round_trip(source), source);
+}
+
+#[test]
+fn nested_blocks_are_indented() {
+    let source = "\
+workspace main {
+x = 0;
+if x > 1 {
+while x < 10 {
+x++;
+}
+} else {
+say(\"small\");
+}
+}
+";
+    assert_eq!(
+        assert_round_trip(source),
+        "\
+workspace main {
+    x = 0;
+    if x > 1 {
+        while x < 10 {
+            x++;
+        }
+    } else {
+        say(\"small\");
+    }
+}
+"
+    );
+}
+
+#[test]
+fn long_lists_wrap_one_item_per_line() {
+    let mut options = FormatOptions::default();
+    options.max_width = 30;
+    let source = "workspace main {\n    files = [\"alpha.c\", \"beta.c\", \"gamma.c\"];\n    say(files);\n}\n";
+    let formatted = format_with(source, &options);
+    assert_eq!(
+        formatted,
+        "\
+workspace main {
+    files = [
+        \"alpha.c\",
+        \"beta.c\",
+        \"gamma.c\"
+    ];
+    say(files);
+}
+"
+    );
+    assert_eq!(format_with(&formatted, &options), formatted);
+}
+
+#[test]
+fn one_blank_line_is_kept_between_statements() {
+    let source = "workspace main {\n    x = 1;\n\n\n\n    say(x);\n}\n";
+    assert_eq!(
+        assert_round_trip(source),
+        "workspace main {\n    x = 1;\n\n    say(x);\n}\n"
+    );
+}
+
+#[test]
+fn long_calls_wrap_one_argument_per_line() {
+    let mut options = FormatOptions::default();
+    options.max_width = 30;
+    let source = "workspace main {\n    compile(\"alpha.c\", \"beta.c\", \"gamma.c\");\n}\n";
+    let formatted = format_with(source, &options);
+    assert_eq!(
+        formatted,
+        "\
+workspace main {
+    compile(
+        \"alpha.c\",
+        \"beta.c\",
+        \"gamma.c\",
+    );
+}
+"
+    );
+    assert_eq!(format_with(&formatted, &options), formatted);
+}
+
+#[test]
+fn number_literals_keep_their_source_text() {
+    let source = "\
+workspace main {
+    big = 100000000000000000000;
+    small = 0.00000001;
+    price = 1.50;
+    whole = 2.0;
+    say(big, small, price, whole);
+}
+";
+    assert_eq!(assert_round_trip(source), source);
+}
+
+#[test]
+fn return_values_are_kept() {
+    let source = "\
+stage pick(flag) {
+    if flag {
+        return [1, 2];
+    }
+    return null;
+}
+";
+    assert_eq!(assert_round_trip(source), source);
+}
@@ -0,0 +1,141 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use mainstage_core::analyzer::lints::{
+    UNKNOWN_LINT, UNREACHABLE_CODE, UNUSED_STAGE, UNUSED_VARIABLE,
+};
+use mainstage_core::analyzer::{LintDiagnostic, LintLevel, LintLevels, check, check_with};
+use mainstage_core::prelude::*;
+
+fn parse(source: &str) -> AstNode {
+    let script = Script::from_source(PathBuf::from("test.ms"), source.to_string());
+    generate_ast_from_source(&script).expect("source should parse")
+}
+
+fn lints(findings: &[LintDiagnostic]) -> Vec<&str> {
+    findings.iter().map(|d| d.lint).collect()
+}
+
+#[test]
+fn unused_stage_is_reported() {
+    let ast = parse("workspace main { build(); }\nstage build() {}\nstage clean() {}\n");
+    let findings = check(&ast);
+    assert_eq!(lints(&findings), [UNUSED_STAGE]);
+    assert_eq!(
+        findings[0].message(),
+        "Stage 'clean' is never called from workspace 'main'."
+    );
+}
+
+#[test]
+fn unused_stage_starts_from_the_entrypoint_attribute() {
+    let ast = parse(
+        "workspace first { first_only(); }\n\
+         [entrypoint]\n\
+         workspace second { second_only(); }\n\
+         stage first_only() {}\n\
+         stage second_only() {}\n",
+    );
+    let findings = check(&ast);
+    assert_eq!(lints(&findings), [UNUSED_STAGE]);
+    assert_eq!(
+        findings[0].message(),
+        "Stage 'first_only' is never called from workspace 'second'."
+    );
+
+    let ast = parse("[entrypoint] project app { build(); }\nstage build() {}\n");
+    assert!(check(&ast).is_empty());
+}
+
+#[test]
+fn scripts_without_an_entry_point_are_libraries() {
+    let ast = parse("stage build() {}\n");
+    assert!(check(&ast).is_empty());
+}
+
+#[test]
+fn unused_variable_is_reported() {
+    let ast = parse("workspace main {\n    x = 1;\n    y = 2;\n    _z = 3;\n    say(y);\n}\n");
+    let findings = check(&ast);
+    assert_eq!(lints(&findings), [UNUSED_VARIABLE]);
+    assert_eq!(
+        findings[0].message(),
+        "Variable 'x' is assigned but never read."
+    );
+}
+
+#[test]
+fn unreachable_code_is_reported_once_per_block() {
+    let ast = parse(
+        "workspace main { build(); }\n\
+         stage build() {\n    return 1;\n    say(1);\n    say(2);\n}\n",
+    );
+    let findings = check(&ast);
+    assert_eq!(lints(&findings), [UNREACHABLE_CODE]);
+    assert_eq!(findings[0].location().map(|l| l.line), Some(4));
+}
+
+#[test]
+fn unknown_lint_in_allow_is_reported() {
+    let ast = parse("workspace main {\n    @allow(unused_varaible)\n    say(1);\n}\n");
+    let findings = check(&ast);
+    assert_eq!(lints(&findings), [UNKNOWN_LINT]);
+    assert_eq!(
+        findings[0].message(),
+        "Unknown lint 'unused_varaible' in @allow."
+    );
+}
+
+#[test]
+fn allow_attribute_suppresses_findings_inside_it() {
+    let ast = parse(
+        "workspace main {\n\
+         \x20   @allow(unused_variable)\n\
+         \x20   x = 1;\n\
+         \x20   y = 2;\n\
+         }\n\
+         @allow(unused)\n\
+         stage helper() {}\n",
+    );
+    let findings = check(&ast);
+    assert_eq!(lints(&findings), [UNUSED_VARIABLE]);
+    assert_eq!(
+        findings[0].message(),
+        "Variable 'y' is assigned but never read."
+    );
+}
+
+#[test]
+fn levels_allow_and_deny_findings() {
+    let ast = parse("workspace main { x = 1; }\nstage helper() {}\n");
+
+    let mut levels = LintLevels::default();
+    levels.set(UNUSED_STAGE, LintLevel::Allow);
+    levels.set(UNUSED_VARIABLE, LintLevel::Deny);
+    let findings = check_with(&ast, &levels);
+    assert_eq!(lints(&findings), [UNUSED_VARIABLE]);
+    assert_eq!(findings[0].level(), Level::Error);
+}
+
+#[test]
+fn individual_lints_take_precedence_over_groups_in_a_table() {
+    let table = BTreeMap::from([
+        ("unused".to_string(), LintLevel::Deny),
+        ("unused_stage".to_string(), LintLevel::Allow),
+        ("all".to_string(), LintLevel::Allow),
+    ]);
+    let mut levels = LintLevels::default();
+    levels.apply(&table);
+    assert_eq!(levels.level(UNUSED_STAGE), LintLevel::Allow);
+    assert_eq!(levels.level(UNUSED_VARIABLE), LintLevel::Deny);
+    assert_eq!(levels.level(UNREACHABLE_CODE), LintLevel::Allow);
+}
+
+#[test]
+fn later_settings_override_earlier_ones() {
+    let mut levels = LintLevels::default();
+    assert_eq!(levels.level(UNUSED_STAGE), LintLevel::Warn);
+    levels.set(UNUSED_STAGE, LintLevel::Deny);
+    levels.set("unused", LintLevel::Allow);
+    assert_eq!(levels.level(UNUSED_STAGE), LintLevel::Allow);
+}
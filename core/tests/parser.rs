@@ -0,0 +1,45 @@
+use std::path::PathBuf;
+
+use mainstage_core::prelude::*;
+
+fn parse(source: &str) -> Result<AstNode, Box<dyn MainstageErrorExt>> {
+    let script = Script::from_source(PathBuf::from("test.ms"), source.to_string());
+    generate_ast_from_source(&script)
+}
+
+fn error_message(source: &str) -> String {
+    parse(source)
+        .expect_err("source should not parse")
+        .message()
+}
+
+#[test]
+fn retry_accepts_positive_and_runtime_counts() {
+    assert!(parse("workspace main { retry (3) { say(1); } }").is_ok());
+    assert!(parse("workspace main { retry (2 * 2 - 3) { say(1); } }").is_ok());
+    assert!(parse("workspace main { n = 2; retry (n) { say(n); } }").is_ok());
+}
+
+#[test]
+fn retry_rejects_counts_below_one() {
+    assert!(
+        error_message("workspace main { retry (0) { say(1); } }")
+            .ends_with("Retry attempts must be at least 1, found 0.")
+    );
+    assert!(
+        error_message("workspace main { retry (-1) { say(1); } }")
+            .ends_with("Retry attempts must be at least 1, found -1.")
+    );
+    assert!(
+        error_message("workspace main { retry (2 - 3) { say(1); } }")
+            .ends_with("Retry attempts must be at least 1, found -1.")
+    );
+}
+
+#[test]
+fn retry_rejects_a_repeated_backoff() {
+    assert!(
+        error_message(r#"workspace main { retry (2, backoff="1s", backoff="2s") { say(1); } }"#)
+            .ends_with("Retry option 'backoff' is given more than once.")
+    );
+}
@@ -0,0 +1,202 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use mainstage_core::prelude::*;
+use mainstage_core::project::{Project, resolve_input};
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir =
+        std::env::temp_dir().join(format!("mainstage-project-{}-{}", std::process::id(), name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).expect("temp dir is writable");
+    dir
+}
+
+fn write(dir: &Path, name: &str, content: &str) -> PathBuf {
+    let path = dir.join(name);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).unwrap();
+    }
+    fs::write(&path, content).unwrap();
+    path
+}
+
+fn file_names(project: &Project, dir: &Path) -> Vec<String> {
+    project
+        .files
+        .iter()
+        .map(|f| {
+            f.script
+                .path
+                .strip_prefix(dir)
+                .unwrap_or(&f.script.path)
+                .to_string_lossy()
+                .replace('\\', "/")
+        })
+        .collect()
+}
+
+fn messages(errors: &[Box<dyn MainstageErrorExt>]) -> Vec<String> {
+    errors.iter().map(|e| e.message()).collect()
+}
+
+#[test]
+fn resolve_input_keeps_a_script_as_is() {
+    let dir = temp_dir("resolve-file");
+    let script = write(&dir, "build.ms", "workspace main {}\n");
+    write(&dir, "other.ms", "workspace other {}\n");
+    assert_eq!(resolve_input(&script).unwrap(), [script]);
+}
+
+#[test]
+fn resolve_input_lists_the_scripts_in_a_directory() {
+    let dir = temp_dir("resolve-dir");
+    let b = write(&dir, "b.ms", "stage b() {}\n");
+    let a = write(&dir, "a.ms", "stage a() {}\n");
+    write(&dir, "notes.txt", "not a script\n");
+    write(&dir, "nested/c.ms", "stage c() {}\n");
+    assert_eq!(resolve_input(&dir).unwrap(), [a, b]);
+}
+
+#[test]
+fn resolve_input_uses_the_configured_script() {
+    let dir = temp_dir("resolve-config");
+    write(&dir, "a.ms", "stage a() {}\n");
+    write(&dir, "mainstage.toml", "script = \"ci/main.ms\"\n");
+    let expected = [dir.join("ci/main.ms")];
+    assert_eq!(resolve_input(&dir).unwrap(), expected);
+    assert_eq!(
+        resolve_input(&dir.join("mainstage.toml")).unwrap(),
+        expected
+    );
+}
+
+#[test]
+fn resolve_input_reports_a_directory_without_scripts() {
+    let dir = temp_dir("resolve-empty");
+    write(&dir, "README.md", "nothing to build\n");
+    let err = resolve_input(&dir).expect_err("there are no scripts");
+    assert_eq!(
+        err.message(),
+        format!("No .ms scripts found in {}.", dir.display())
+    );
+}
+
+#[test]
+fn load_follows_includes_transitively() {
+    let dir = temp_dir("transitive");
+    let main = write(
+        &dir,
+        "main.ms",
+        "include \"lib/a.ms\";\ninclude \"c.ms\";\nworkspace main { a(); b(); c(); }\n",
+    );
+    // `b.ms` is resolved against `lib/`, the directory of the file including it.
+    write(&dir, "lib/a.ms", "include \"b.ms\";\nstage a() {}\n");
+    write(&dir, "lib/b.ms", "stage b() {}\n");
+    write(&dir, "c.ms", "stage c() {}\n");
+
+    let (project, errors) = Project::load(&[main]);
+    assert!(errors.is_empty(), "{:?}", messages(&errors));
+    assert_eq!(
+        file_names(&project, &dir),
+        ["main.ms", "lib/a.ms", "lib/b.ms", "c.ms"]
+    );
+}
+
+#[test]
+fn load_reads_each_file_once_in_an_include_cycle() {
+    let dir = temp_dir("cycle");
+    let a = write(&dir, "a.ms", "include \"b.ms\";\nworkspace main { b(); }\n");
+    write(&dir, "b.ms", "include \"./a.ms\";\nstage b() {}\n");
+
+    let (project, errors) = Project::load(&[a]);
+    assert!(errors.is_empty(), "{:?}", messages(&errors));
+    assert_eq!(file_names(&project, &dir), ["a.ms", "b.ms"]);
+}
+
+#[test]
+fn load_reads_a_shared_include_once() {
+    let dir = temp_dir("shared");
+    let a = write(
+        &dir,
+        "a.ms",
+        "include \"common.ms\";\nworkspace a { common(); }\n",
+    );
+    let b = write(
+        &dir,
+        "b.ms",
+        "include \"common.ms\";\nworkspace b { common(); }\n",
+    );
+    write(&dir, "common.ms", "stage common() {}\n");
+
+    let (project, errors) = Project::load(&[a, b]);
+    assert!(errors.is_empty(), "{:?}", messages(&errors));
+    assert_eq!(file_names(&project, &dir), ["a.ms", "common.ms", "b.ms"]);
+}
+
+#[test]
+fn load_reports_a_missing_include_at_the_include() {
+    let dir = temp_dir("missing-include");
+    let main = write(
+        &dir,
+        "main.ms",
+        "workspace main {}\ninclude \"missing.ms\";\n",
+    );
+
+    let (project, errors) = Project::load(&[main]);
+    assert_eq!(
+        messages(&errors),
+        ["Included file 'missing.ms' was not found."]
+    );
+    assert_eq!(errors[0].location().expect("error is located").line, 2);
+    assert_eq!(project.files.len(), 1);
+}
+
+#[test]
+fn load_reports_duplicate_declarations_across_files() {
+    let dir = temp_dir("duplicate");
+    let main = write(
+        &dir,
+        "main.ms",
+        "include \"other.ms\";\nworkspace main { build(); }\nstage build() {}\n",
+    );
+    let other = write(
+        &dir,
+        "other.ms",
+        "// Same names as main.ms.\nstage build() {}\n\nworkspace main {}\n",
+    );
+
+    let (_, errors) = Project::load(std::slice::from_ref(&main));
+    assert_eq!(
+        messages(&errors),
+        [
+            format!("Stage 'build' is already declared in {}.", main.display()),
+            format!(
+                "Workspace 'main' is already declared in {}.",
+                main.display()
+            ),
+        ]
+    );
+    let locations: Vec<_> = errors
+        .iter()
+        .map(|e| e.location().expect("error is located"))
+        .collect();
+    assert_eq!(locations[0].file, other.display().to_string());
+    assert_eq!((locations[0].line, locations[1].line), (2, 4));
+}
+
+#[test]
+fn load_keeps_going_after_a_broken_file() {
+    let dir = temp_dir("broken");
+    let main = write(
+        &dir,
+        "main.ms",
+        "include \"broken.ms\";\ninclude \"fine.ms\";\nworkspace main {}\n",
+    );
+    write(&dir, "broken.ms", "stage broken( {\n");
+    write(&dir, "fine.ms", "stage fine() {}\n");
+
+    let (project, errors) = Project::load(&[main]);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(file_names(&project, &dir), ["main.ms", "fine.ms"]);
+}
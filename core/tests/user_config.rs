@@ -0,0 +1,138 @@
+use std::fs;
+use std::path::PathBuf;
+
+use mainstage_core::prelude::*;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "mainstage-user-config-{}-{}",
+        std::process::id(),
+        name
+    ));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).expect("temp dir is writable");
+    dir
+}
+
+#[test]
+fn missing_file_yields_defaults() {
+    let path = temp_dir("missing").join("config.toml");
+    let config = UserConfig::load(&path).expect("a missing file is not an error");
+    assert_eq!(config.path, Some(path));
+    assert!(config.plugin_dirs.is_empty());
+    assert_eq!(config.color, None);
+    assert_eq!(config.get("color"), None);
+}
+
+#[test]
+fn set_and_get_nested_keys() {
+    let mut config = UserConfig::default();
+    config
+        .set("compilers.c", "clang")
+        .expect("compilers is a table");
+    config
+        .set("compilers.cpp", "\"g++\"")
+        .expect("quoted strings are TOML");
+    config
+        .set("plugin_dirs", "[\"/opt/plugins\"]")
+        .expect("arrays are TOML");
+
+    assert_eq!(config.get("compilers.c").as_deref(), Some("clang"));
+    assert_eq!(config.get("compilers.cpp").as_deref(), Some("g++"));
+    assert_eq!(config.compilers["c"], "clang");
+    assert_eq!(config.plugin_dirs, [PathBuf::from("/opt/plugins")]);
+    assert_eq!(
+        config.get("plugin_dirs").as_deref(),
+        Some("[\"/opt/plugins\"]")
+    );
+    assert_eq!(config.get("compilers.rust"), None);
+    assert_eq!(config.get("compilers.c.version"), None);
+}
+
+#[test]
+fn set_overwrites_an_existing_value() {
+    let mut config = UserConfig::default();
+    config.set("color", "always").unwrap();
+    config.set("color", "never").unwrap();
+    assert_eq!(config.color.as_deref(), Some("never"));
+    assert_eq!(config.get("color").as_deref(), Some("never"));
+}
+
+#[test]
+fn set_rejects_overwriting_a_table_with_a_scalar() {
+    let mut config = UserConfig::default();
+    config.set("compilers.c", "clang").unwrap();
+    let err = config
+        .set("compilers", "clang")
+        .expect_err("compilers must stay a table");
+    assert!(err.message().starts_with("Invalid "), "{}", err.message());
+    assert_eq!(config.get("compilers.c").as_deref(), Some("clang"));
+}
+
+#[test]
+fn set_rejects_a_key_below_a_scalar() {
+    let mut config = UserConfig::default();
+    config.set("color", "auto").unwrap();
+    let err = config
+        .set("color.mode", "always")
+        .expect_err("color is not a table");
+    assert_eq!(
+        err.message(),
+        "Cannot set 'color.mode': 'color' is not a table."
+    );
+    assert_eq!(config.get("color").as_deref(), Some("auto"));
+}
+
+#[test]
+fn set_validates_the_result() {
+    let mut config = UserConfig::default();
+    let err = config.set("color", "sometimes").expect_err("not a choice");
+    assert_eq!(
+        err.message(),
+        "color must be one of auto, always or never, found 'sometimes'."
+    );
+    assert!(config.set("colour", "auto").is_err());
+    assert_eq!(config.color, None);
+}
+
+#[test]
+fn save_and_load_round_trip() {
+    let dir = temp_dir("round-trip");
+    let path = dir.join("mainstage").join("config.toml");
+    let mut config = UserConfig::load(&path).unwrap();
+    config.set("color", "never").unwrap();
+    config.set("compilers.c", "gcc").unwrap();
+    config.set("cache_dir", "/tmp/mainstage-cache").unwrap();
+    config.save().expect("the directory is created on save");
+
+    let loaded = UserConfig::load(&path).expect("saved file should load");
+    assert_eq!(loaded, config);
+    assert_eq!(loaded.get("compilers.c").as_deref(), Some("gcc"));
+    assert_eq!(
+        loaded.cache_dir,
+        Some(PathBuf::from("/tmp/mainstage-cache"))
+    );
+}
+
+#[test]
+fn save_without_a_path_fails() {
+    let mut config = UserConfig::default();
+    config.set("color", "auto").unwrap();
+    let err = config.save().expect_err("there is nowhere to save to");
+    assert_eq!(
+        err.message(),
+        "No location to save the user configuration to."
+    );
+}
+
+#[test]
+fn load_rejects_an_invalid_file() {
+    let dir = temp_dir("invalid");
+    let path = dir.join("config.toml");
+    fs::write(&path, "color = \"purple\"\n").unwrap();
+    let err = UserConfig::load(&path).expect_err("purple is not a choice");
+    assert_eq!(
+        err.message(),
+        "color must be one of auto, always or never, found 'purple'."
+    );
+}